@@ -0,0 +1,94 @@
+#![allow(clippy::needless_return)] // Matches the explicit return style used in lib.rs.
+
+// Compares the old simple-grep search (split the file with lines(), then str::contains on every line) against PatternMatcher::matching_lines, which searches the whole buffer first.
+// Run with: cargo bench --bench literal_search
+// A file can be passed to benchmark real data: cargo bench --bench literal_search -- /var/log/syslog error
+
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use lozgrep::match_many_patterns::PatternMatcher;
+
+const ROUNDS: u32 = 10;
+
+fn main() {
+    let arguments: Vec<String> = env::args().skip(1).filter(|argument| argument != "--bench").collect(); // cargo bench passes --bench to the binary.
+
+    let cases: Vec<(String, String, String)> = if arguments.len() == 2 { // (name, buffer, query)
+        let buffer: String = fs::read_to_string(&arguments[0]).expect("the benchmark file has to be readable UTF-8");
+        vec![(arguments[0].clone(), buffer, arguments[1].clone())]
+    } else {
+        vec![
+            ("rare hit, 64 MiB".to_string(), generate_buffer(64 * 1024 * 1024, 100_000), "needle_in_the_haystack".to_string()),
+            ("frequent hit, 64 MiB".to_string(), generate_buffer(64 * 1024 * 1024, 10), "needle_in_the_haystack".to_string()),
+            ("no hit, 64 MiB".to_string(), generate_buffer(64 * 1024 * 1024, 0), "needle_in_the_haystack".to_string()),
+            ("short query, 64 MiB".to_string(), generate_buffer(64 * 1024 * 1024, 1_000), "zq".to_string()),
+        ]
+    };
+
+    println!("{:<24}{:>16}{:>16}{:>10}{:>10}", "case", "per line", "whole buffer", "speedup", "lines");
+
+    for (name, buffer, query) in &cases {
+        let matcher: PatternMatcher = PatternMatcher::build(vec![query.clone()]);
+
+        let per_line_result: Vec<&str> = per_line(buffer, query);
+        let whole_buffer_result: Vec<&str> = matcher.matching_lines(buffer).collect();
+        assert_eq!(per_line_result, whole_buffer_result, "both approaches have to find the same lines for {}", name); // A fast wrong answer is not a result.
+
+        let per_line_time: Duration = time_it(|| per_line(buffer, query).len());
+        let whole_buffer_time: Duration = time_it(|| matcher.matching_lines(buffer).count());
+
+        println!("{:<24}{:>13.2} ms{:>13.2} ms{:>9.1}x{:>10}", name, milliseconds(per_line_time), milliseconds(whole_buffer_time), per_line_time.as_secs_f64() / whole_buffer_time.as_secs_f64(), whole_buffer_result.len());
+    }
+}
+
+fn per_line<'a>(borrow_buffer: &'a str, borrow_query: &str) -> Vec<&'a str> { // The simple-grep loop before the whole buffer search.
+    let mut results_that_match_query: Vec<&str> = Vec::new();
+
+    for line in borrow_buffer.lines() {
+        if line.contains(borrow_query) { results_that_match_query.push(line); }
+    }
+
+    return results_that_match_query;
+}
+
+fn time_it(borrow_search: impl Fn() -> usize) -> Duration { // The fastest of a few rounds, so a busy machine does not skew the result as much.
+    let mut fastest: Duration = Duration::MAX;
+
+    for _ in 0..ROUNDS {
+        let start: Instant = Instant::now();
+        black_box(borrow_search());
+        fastest = fastest.min(start.elapsed());
+    }
+
+    return fastest;
+}
+
+fn milliseconds(borrow_duration: Duration) -> f64 {
+    return borrow_duration.as_secs_f64() * 1000.0;
+}
+
+fn generate_buffer(borrow_size: usize, borrow_hit_every: usize) -> String { // Log-like lines of pseudo random words. Every borrow_hit_every lines holds the needle, 0 means never.
+    let words: [&str; 12] = ["INFO", "request", "served", "in", "ms", "user", "session", "opened", "closed", "cache", "miss", "GET /index.html"];
+    let mut buffer: String = String::with_capacity(borrow_size + 128);
+    let mut seed: u64 = 0x2545F4914F6CDD1D;
+    let mut line_number: usize = 0;
+
+    while buffer.len() < borrow_size {
+        line_number += 1;
+
+        for _ in 0..10 {
+            seed ^= seed << 13; // xorshift, the same data on every run without a random crate.
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            buffer.push_str(words[(seed % words.len() as u64) as usize]);
+            buffer.push(' ');
+        }
+
+        if borrow_hit_every != 0 && line_number.is_multiple_of(borrow_hit_every) { buffer.push_str("needle_in_the_haystack zq"); }
+        buffer.push('\n');
+    }
+
+    return buffer;
+}
@@ -0,0 +1,68 @@
+#![allow(clippy::needless_return)] // Matches the explicit return style used in lib.rs.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn main() { // Captures the build information shown by lozgrep --version --verbose. Every value is passed to the compiler as an environment variable and read with env!.
+    let features: Vec<String> = enabled_features();
+    let target: String = env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    let git_commit: String = git_commit();
+
+    println!("cargo:rustc-env=LOZGREP_FEATURES={}", if features.is_empty() { "none".to_string() } else { features.join(", ") });
+    println!("cargo:rustc-env=LOZGREP_TARGET={}", target);
+    println!("cargo:rustc-env=LOZGREP_GIT_COMMIT={}", git_commit);
+    if let Some(build_date) = build_date() { println!("cargo:rustc-env=LOZGREP_BUILD_DATE={}", build_date); }
+
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=.git/HEAD"); // A new commit or a different branch has to be picked up, otherwise the commit would be stale.
+    if let Ok(head) = fs::read_to_string(".git/HEAD") {
+        if let Some(reference) = head.trim().strip_prefix("ref: ") { watch_reference(reference); }
+    }
+}
+
+fn watch_reference(borrow_reference: &str) { // A path that does not exist makes cargo run build.rs on every build, so only files that are there are watched.
+    let loose_reference: String = format!(".git/{}", borrow_reference);
+    if Path::new(&loose_reference).is_file() {
+        println!("cargo:rerun-if-changed={}", loose_reference);
+        return;
+    }
+
+    if Path::new(".git/packed-refs").is_file() { println!("cargo:rerun-if-changed=.git/packed-refs"); } // After git gc, and in fresh clones, the branch is a line of packed-refs.
+    if let Some(reference_directory) = Path::new(&loose_reference).parent().filter(|directory| directory.is_dir()) { println!("cargo:rerun-if-changed={}", reference_directory.display()); } // The next commit writes the loose file again, that changes the directory.
+}
+
+fn enabled_features() -> Vec<String> { // Cargo sets CARGO_FEATURE_<NAME> for every enabled feature. CARGO_FEATURE_SEARCH_ZIP becomes search-zip.
+    let mut features: Vec<String> = env::vars().filter_map(|(name, _)| name.strip_prefix("CARGO_FEATURE_").map(|feature| feature.to_lowercase().replace('_', "-"))).collect();
+    features.sort();
+    return features;
+}
+
+fn git_commit() -> String { // The short hash of HEAD. "unknown" if the source is not a git checkout or git is not installed.
+    match Command::new("git").args(["rev-parse", "--short", "HEAD"]).output() {
+        Ok(output) if output.status.success() => { return String::from_utf8_lossy(&output.stdout).trim().to_string(); }
+        _ => { return "unknown".to_string(); }
+    }
+}
+
+fn build_date() -> Option<String> { // YYYY-MM-DD in UTC, from SOURCE_DATE_EPOCH. None if it is not set, because the clock would only tell when build.rs last ran, not when the binary was built.
+    let seconds: u64 = env::var("SOURCE_DATE_EPOCH").ok().and_then(|epoch| epoch.parse::<u64>().ok())?;
+
+    let (year, month, day) = civil_date((seconds / 86400) as i64);
+    return Some(format!("{:04}-{:02}-{:02}", year, month, day));
+}
+
+fn civil_date(days_since_epoch: i64) -> (i64, u32, u32) { // Days since 1970-01-01 to (year, month, day), without pulling in a date crate. The years are counted from March, so the leap day is the last day of the year.
+    let shifted_days: i64 = days_since_epoch + 719468; // Days since 0000-03-01.
+    let era: i64 = shifted_days.div_euclid(146097); // 400 year cycles.
+    let day_of_era: i64 = shifted_days.rem_euclid(146097);
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index: i64 = (5 * day_of_year + 2) / 153; // 0 is March.
+    let day: u32 = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month: u32 = if month_index < 10 { (month_index + 3) as u32 } else { (month_index - 9) as u32 };
+    let year: i64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return (year, month, day);
+}
@@ -1,3 +1,11 @@
+#![allow(clippy::bool_comparison, clippy::needless_return, clippy::ptr_arg, clippy::len_zero, clippy::assign_op_pattern)] // The code base writes comparisons, returns, counters and borrows out in full.
+
+#[macro_export]
+macro_rules! print_line { // Same as println!, but a reader that went away, like head after its last line, ends the process quietly instead of panicking. Every line lozgrep prints goes through here, so the results and the messages stay in the order they were printed.
+    () => { $crate::print_to_terminal::write_line(format_args!("")) };
+    ($($argument:tt)*) => { $crate::print_to_terminal::write_line(format_args!($($argument)*)) };
+}
+
 pub mod parse_and_build_arguments {
     use std::env;
     use std::process;
@@ -5,310 +13,707 @@ pub mod parse_and_build_arguments {
     use std::fs;
     use std::io;
     use std::path::Path;
+    use crate::filter_by_metadata::{MetadataFilters, build_metadata_filters};
+    use crate::filter_by_file_type::{FileTypeFilter, build_file_type_filter};
+    use crate::match_many_patterns::{PatternMatcher, build_pattern_matcher};
+    use crate::locate_file_names::{DEFAULT_MAX_AGE, parse_max_age};
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OptionScope { // Which command an option belongs to, and which help page it is shown on.
+        General,
+        Grep,
+        Find,
+        Replace,
+        Index,
+        Updatedb,
+        Search, // Both grep and find.
+        AllCommands,
+    }
+
+    impl OptionScope {
+        pub fn applies_to(&self, borrow_command_scope: OptionScope) -> bool { // True if an option of this scope can be used with the command of borrow_command_scope.
+            match self {
+                OptionScope::General | OptionScope::AllCommands => { return true; }
+                OptionScope::Search => { return borrow_command_scope == OptionScope::Grep || borrow_command_scope == OptionScope::Find; }
+                scope => { return *scope == borrow_command_scope; }
+            }
+        }
+    }
+
+    pub struct OptionSpec { // One row of the option table. The tokenizer, the help menu, the man page and the shell completions are all built from this table.
+        pub long: &'static str,
+        pub short: &'static str,
+        pub takes_value: bool, // If true, the option owns a value. The value is the next argument (--query foo), or comes after = (--query=foo), or is stuck to the short option (-qfoo).
+        pub value_name: &'static str, // Shown in the man page and used by the shell completions. Empty if the option does not take a value.
+        pub scope: OptionScope,
+        pub description: &'static str,
+    }
+
+    pub const OPTION_TABLE: [OptionSpec; 43] = [ // These are all the valid options.
+        OptionSpec { long: "--help", short: "-h", takes_value: false, value_name: "", scope: OptionScope::General, description: "Prints the help menu. \"lozgrep help COMMAND\" prints the help for one command: lozgrep help grep." },
+        OptionSpec { long: "--version", short: "-ver", takes_value: false, value_name: "", scope: OptionScope::General, description: "Prints the current version. With --verbose, also prints the cargo features, target, git commit and, if SOURCE_DATE_EPOCH was set for the build, the build date." },
+        OptionSpec { long: "--verbose", short: "-v", takes_value: false, value_name: "", scope: OptionScope::General, description: "Prints output statements while the process is running." },
+        OptionSpec { long: "--query", short: "-q", takes_value: true, value_name: "QUERY", scope: OptionScope::General, description: "The term you are searching for follows this option." },
+        OptionSpec { long: "--patterns-file", short: "-f", takes_value: true, value_name: "FILE", scope: OptionScope::AllCommands, description: "Every line of the file is a pattern, a line or name matches if it contains any of them. Empty lines are skipped. Can be used with or instead of the query." },
+        OptionSpec { long: "--path", short: "-p", takes_value: true, value_name: "PATH", scope: OptionScope::General, description: "The path you are searching follows this option. Can be passed more than once." },
+        OptionSpec { long: "--simple-grep", short: "-sg", takes_value: false, value_name: "", scope: OptionScope::Grep, description: "Same as the grep command. Searches the contents of a file." },
+        OptionSpec { long: "--simple-find", short: "-sf", takes_value: false, value_name: "", scope: OptionScope::Find, description: "Same as the find command. Searches for a file or directory name." },
+        OptionSpec { long: "--simple-replace", short: "-sr", takes_value: false, value_name: "", scope: OptionScope::Replace, description: "Same as the replace command. Replaces the query in the contents of a file." },
+        OptionSpec { long: "--replacement", short: "-rw", takes_value: true, value_name: "TEXT", scope: OptionScope::Replace, description: "The text the query is replaced with. Can also be given as the value after the query: lozgrep replace old new file. With a patterns file, the match that starts first is replaced, and the longest one if several start at the same place." },
+        OptionSpec { long: "--in-place", short: "-i", takes_value: false, value_name: "", scope: OptionScope::Replace, description: "replace rewrites the files instead of printing a unified diff. Every file is written to a temporary file first and then renamed over the original, the permissions are kept." },
+        OptionSpec { long: "--simple-index", short: "-si", takes_value: false, value_name: "", scope: OptionScope::Index, description: "Same as the index command. Builds the trigram index of a directory, or updates it if it already exists." },
+        OptionSpec { long: "--indexed", short: "-ix", takes_value: false, value_name: "", scope: OptionScope::Grep, description: "grep searches directories that were indexed with the index command. Only the files that contain every trigram of the query are read. Changed files are indexed again before the search." },
+        OptionSpec { long: "--multiline", short: "-ml", takes_value: false, value_name: "", scope: OptionScope::Grep, description: "A match can go over line breaks. Every line the match covers is printed, with its line number. A literal query can hold a line break, a regular expression can use \\n." },
+        OptionSpec { long: "--only-matching", short: "-om", takes_value: false, value_name: "", scope: OptionScope::Grep, description: "grep prints every match on its own line, instead of the whole line it is on." },
+        OptionSpec { long: "--output", short: "-ot", takes_value: true, value_name: "TEMPLATE", scope: OptionScope::Grep, description: "grep prints the template for every match, filled in from the capture groups: '$1', '${user}@${host}'. Needs --regex." },
+        OptionSpec { long: "--watch", short: "-wa", takes_value: false, value_name: "", scope: OptionScope::Grep, description: "grep keeps running. A file is followed like tail -f, the new lines that match are printed. A directory is watched, and every file that is changed in it is followed from then on. Stop it with Ctrl+C." },
+        OptionSpec { long: "--max-count", short: "-mc", takes_value: true, value_name: "NUMBER", scope: OptionScope::Grep, description: "grep stops reading a file after this many matching lines. With --multiline it counts blocks, with --only-matching or --output it counts matches." },
+        OptionSpec { long: "--limit", short: "-li", takes_value: true, value_name: "NUMBER", scope: OptionScope::Search, description: "grep and find stop after printing this many results in total, over every path." },
+        OptionSpec { long: "--quiet", short: "-qu", takes_value: false, value_name: "", scope: OptionScope::Search, description: "grep and find print nothing, not even errors, and stop at the first match. The exit code is 0 if something was found, 1 if not." },
+        OptionSpec { long: "--simple-updatedb", short: "-su", takes_value: false, value_name: "", scope: OptionScope::Updatedb, description: "Same as the updatedb command. Saves every path under a directory to the filename database, for find --locate." },
+        OptionSpec { long: "--locate", short: "-lo", takes_value: false, value_name: "", scope: OptionScope::Find, description: "find reads the paths from the filename database instead of walking the directory. The database of the directory, or of any directory above it, is used." },
+        OptionSpec { long: "--max-age", short: "-ma", takes_value: true, value_name: "DURATION", scope: OptionScope::Find, description: "With locate, a warning is printed if the filename database is older than this. Units are s, m, h, d, w. The default is 1d." },
+        OptionSpec { long: "--regex", short: "-re", takes_value: false, value_name: "", scope: OptionScope::AllCommands, description: "The query and the lines of the patterns file are regular expressions. replace can use the capture groups in the replacement: $1, ${1}, ${name}." },
+        OptionSpec { long: "--search-zip", short: "-z", takes_value: false, value_name: "", scope: OptionScope::Grep, description: "grep decompresses .gz, .bz2, .xz and .zst files before searching them. The format is found from the first bytes of the file, or from the extension. The matches are printed with the path of the compressed file." },
+        OptionSpec { long: "--search-archives", short: "-ar", takes_value: false, value_name: "", scope: OptionScope::AllCommands, description: "Looks inside of tar (.tar, .tar.gz, .tar.bz2, .tar.xz, .tar.zst) and zip files. Every member has a virtual path: release.tar.gz!/etc/app.conf. find matches the member names, grep searches the member contents. replace does not change archives." },
+        OptionSpec { long: "--follow", short: "-fo", takes_value: false, value_name: "", scope: OptionScope::AllCommands, description: "Follows symbolic links that are found inside of a directory. simple-find descends into linked directories, grep --watch searches linked files. A link named on the command line is always followed." },
+        OptionSpec { long: "--one-file-system", short: "-ofs", takes_value: false, value_name: "", scope: OptionScope::Find, description: "simple-find does not descend into directories that are on a different file system (/proc, /sys, network mounts)." },
+        OptionSpec { long: "--quiet-errors", short: "-qe", takes_value: false, value_name: "", scope: OptionScope::Find, description: "simple-find does not print the entries it was not able to read (permission denied, symbolic link loops)." },
+        OptionSpec { long: "--error-summary", short: "-es", takes_value: false, value_name: "", scope: OptionScope::Find, description: "simple-find prints one line at the end with the number of entries it was not able to read." },
+        OptionSpec { long: "--list-errors", short: "-le", takes_value: false, value_name: "", scope: OptionScope::Find, description: "Same as error-summary, but also lists every entry that was not able to be read." },
+        OptionSpec { long: "--size", short: "-sz", takes_value: true, value_name: "SIZE", scope: OptionScope::Find, description: "simple-find only matches files of this size. +10M is larger than, -1k is smaller than, 512 is exactly. Units are k, M, G, T. Two sizes can be joined with a comma: +1k,-10M." },
+        OptionSpec { long: "--mtime", short: "-mt", takes_value: true, value_name: "TIME", scope: OptionScope::Find, description: "simple-find only matches entries modified within (-7d) or before (+7d) a duration. Units are s, m, h, d, w. A path can be used instead of a duration: -/etc/passwd." },
+        OptionSpec { long: "--atime", short: "-at", takes_value: true, value_name: "TIME", scope: OptionScope::Find, description: "Same as mtime, but uses the last access time." },
+        OptionSpec { long: "--user", short: "-us", takes_value: true, value_name: "USER", scope: OptionScope::Find, description: "simple-find only matches entries owned by this user name or user ID." },
+        OptionSpec { long: "--group", short: "-gr", takes_value: true, value_name: "GROUP", scope: OptionScope::Find, description: "simple-find only matches entries owned by this group name or group ID." },
+        OptionSpec { long: "--perm", short: "-pm", takes_value: true, value_name: "MODE", scope: OptionScope::Find, description: "simple-find only matches entries with these permission bits. 644 is exactly, -644 is all of, /022 is any of." },
+        OptionSpec { long: "--type", short: "-t", takes_value: true, value_name: "TYPES", scope: OptionScope::AllCommands, description: "Only files of these types are matched by simple-find, searched by simple-grep or changed by simple-replace. Types are joined with a comma: rust,py." },
+        OptionSpec { long: "--type-not", short: "-tn", takes_value: true, value_name: "TYPES", scope: OptionScope::AllCommands, description: "Files of these types are not matched by simple-find, searched by simple-grep or changed by simple-replace." },
+        OptionSpec { long: "--type-add", short: "-ta", takes_value: true, value_name: "DEFINITION", scope: OptionScope::AllCommands, description: "Makes custom types for type and type-not: name:pattern,pattern. Types are joined with a semicolon: web:*.html,*.css;conf:*.conf." },
+        OptionSpec { long: "--no-config", short: "-nc", takes_value: false, value_name: "", scope: OptionScope::General, description: "Does not read the default options from the config file." },
+        OptionSpec { long: "--generate-man-page", short: "-gmp", takes_value: false, value_name: "", scope: OptionScope::General, description: "Prints the man page in roff format: lozgrep -gmp > lozgrep.1" },
+        OptionSpec { long: "--generate-completions", short: "-gc", takes_value: true, value_name: "SHELL", scope: OptionScope::General, description: "Prints the completion script for bash, zsh or fish: lozgrep -gc bash > /etc/bash_completion.d/lozgrep" },
+    ];
+
+    pub const COMMAND_TABLE: [(&str, &str, OptionScope, &str); 5] = [ // (command, the option it is the same as, the options it takes, description). The command has to be the first argument: lozgrep grep QUERY FILE...
+        ("grep", "--simple-grep", OptionScope::Grep, "Searches the contents of files."),
+        ("find", "--simple-find", OptionScope::Find, "Searches for file and directory names."),
+        ("replace", "--simple-replace", OptionScope::Replace, "Replaces text in the contents of files."),
+        ("index", "--simple-index", OptionScope::Index, "Builds or updates the trigram index of directories."),
+        ("updatedb", "--simple-updatedb", OptionScope::Updatedb, "Saves every path under directories to the filename database."),
+    ];
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SettingSource { // Where a setting came from, from lowest to highest precedence.
+        BuiltIn,
+        ConfigFile,
+        Environment,
+        CommandLine,
+    }
+
+    #[derive(Debug)]
+    pub struct PassedOption { // One option found by tokenize_arguments, read_environment_options or read_config_file_options.
+        pub long: &'static str, // The long name from the option table, so --query and -q are the same option after this point.
+        pub spelling: String, // How the user typed it, only used for error messages.
+        pub value: Option<String>,
+        pub source: SettingSource,
+    }
 
     pub fn build_running_configuration() -> Options {
         let collected_arguments: Vec<String> = env::args().skip(1).collect(); // Will collect passed arguments and put them into a vector. Does not collect the first passed argument, because it is not needed.
-        let possible_options: [&str; 14] = ["--help", "-h", "--version", "-ver", "--verbose", "-v", "--query", "-q", "--path", "-p", "--simple-grep", "-sg", "--simple-find", "-sf"]; // These are all the valid options.
 
-        verify_argument_length(&collected_arguments); // Checks if zero arguments are passed, checks if too many arguments are passed, error in either senario.
+        verify_argument_length(&collected_arguments); // Checks if zero arguments are passed, it is an error.
+
+        if collected_arguments[0] == "help" { return build_help_configuration(&collected_arguments); } // lozgrep help, lozgrep help grep, lozgrep help replace. Nothing else is parsed, so the config file cannot turn the help into a search.
+
+        let (command_option, option_arguments) = split_command(&collected_arguments); // lozgrep grep ..., lozgrep find ... and lozgrep replace ... The command becomes its -sg, -sf or -sr alias, so both spellings work the same from here on.
+        let (mut command_line_options, positional_values) = tokenize_arguments(&option_arguments, ""); // Walks the arguments once, from left to right. Every option takes the value that belongs to it, everything else is a positional value. Creates errors for unknown options and missing values.
+        if let Some(command_option) = command_option { command_line_options.insert(0, command_option); } // lozgrep grep -sg is a duplicate, lozgrep grep -sf is two commands.
+        check_for_duplicate_options(&command_line_options, ""); // Creates an error if the same option is passed twice, in any spelling (-h -h, --help -h).
+        let positional_options: Vec<PassedOption> = positional_values_to_options(&command_line_options, positional_values); // The positional values become --query and --path options, so they replace a query or path from the environment or config file like any other option.
+        command_line_options.extend(positional_options);
+
+        let (environment_options, disabled_by_environment) = read_environment_options(); // LOZGREP_FOLLOW=1, LOZGREP_TYPE_NOT=json. A flag set to 0 turns off the same flag from the config file.
+        let skip_config_file: bool = option_passed(&command_line_options, "--no-config") || option_passed(&environment_options, "--no-config");
+        let (config_options, config_file) = if skip_config_file == true { (Vec::new(), None) } else { read_config_file_options() }; // The config file holds default options.
+        let config_options: Vec<PassedOption> = config_options.into_iter().filter(|option| !disabled_by_environment.contains(&option.long)).collect();
 
-        let validated_options: Vec<String> = verify_options_are_valid(&collected_arguments, &possible_options); // Filters and collects all options (--, -) from the arguments. Compares the filtered options to possible_options to verify the given options. Creates errors if bad options are present. Calls on a function to check for exact duplicate options (-h -h), and creates an error if there are duplicate options. Calls on function to check for logically duplicate options (--help -h), and creates error if there are duplicates.
-        let validated_values = verify_values_are_valid(&collected_arguments, &validated_options); // Parses out the non-option arguments. Verifies that if there are zero non-option arguments, then query and path are not present. Creates errors if there is only one value or more than two values. 
-        
-        if validated_values.len() == 0 { // If validated_vales.len() == 0 and validated_values gets passed into parse_path_and_query, it will cause an error. 
-            let null_query: String = "null".to_string(); // Needed because check_if_the_given_options_work_together requires two Strings to be passed to it.
-            let null_path: String = "null".to_string();
+        let passed_options: Vec<PassedOption> = layer_options(layer_options(config_options, environment_options), command_line_options); // Command line beats environment, environment beats config file, config file beats the built-in defaults.
+        let passed_options: Vec<PassedOption> = keep_options_of_the_command(passed_options); // Every command has its own option set. Creates an error if an option of the other command is passed.
 
-            check_if_the_given_options_work_together(&validated_options, &null_query, &null_path); // Will ignore null strings.
+        let (valid_query, valid_paths) = assign_query_and_paths(&passed_options); // Validates paths. Checks for escape character on the query.
 
-            let running_options: Options = Options::build_options(validated_options, null_query, null_path);
+        let option_values: Vec<(String, String)> = passed_options.iter().filter_map(|option| option.value.clone().map(|value| (option.long.to_string(), value))).collect(); // (option, value) tuples for the filter modules.
+        let metadata_filters: MetadataFilters = build_metadata_filters(&option_values); // Parses and validates the metadata values, creates errors if a value cannot be understood.
+        let file_type_filter: FileTypeFilter = build_file_type_filter(&option_values); // Resolves the type names into file name patterns, creates errors if a type is not known.
+        let pattern_matcher: PatternMatcher = build_pattern_matcher(&valid_query, option_value(&passed_options, "--patterns-file"), option_passed(&passed_options, "--regex")); // The query and every line of the patterns file are searched for at the same time.
 
-            return running_options;
+        check_if_the_given_options_work_together(&passed_options, &valid_query, &valid_paths); // Checks if the passed options work together. May have to add more logic here, if errors appear.
 
-        } else { // validated_values.len() != 0
-            let (valid_query, valid_path) = parse_path_and_query(&collected_arguments, &validated_values); // Creates an error if a non-option value is passed as the first argument. Creates errors if a non-option value is passed behind an option that is not path or query. Parses which value is a query and which value is a path. Validates path. Checks for escape character on the query.
-        
-            check_if_the_given_options_work_together(&validated_options, &valid_query, &valid_path); // Checks if the passed options work together. May have to add more logic here, if errors appear.
-            
-            let running_options: Options = Options::build_options(validated_options, valid_query, valid_path);
+        let help_topic: Option<OptionScope> = COMMAND_TABLE.iter().find(|(_, long, _, _)| option_passed(&passed_options, long)).map(|(_, _, scope, _)| *scope); // --help -sg is the same as lozgrep help grep.
+        let running_options: Options = Options::build_options(&passed_options, valid_query, valid_paths, metadata_filters, file_type_filter, pattern_matcher, config_file, help_topic);
 
-            return running_options;
+        return running_options;
+    }
+
+    fn split_command(borrow_collected_arguments: &Vec<String>) -> (Option<PassedOption>, Vec<String>) { // Returns the command as its alias option, and the arguments after it. Only the first argument can be a command, so lozgrep -sg grep file still searches for grep.
+        if let Some((command, long, _, _)) = COMMAND_TABLE.iter().find(|(command, _, _, _)| *command == borrow_collected_arguments[0]) {
+            let command_option: PassedOption = PassedOption { long, spelling: command.to_string(), value: None, source: SettingSource::CommandLine };
+            return (Some(command_option), borrow_collected_arguments[1..].to_vec());
         }
+
+        return (None, borrow_collected_arguments.clone());
     }
 
-    fn verify_argument_length(borrow_collected_arguments: &Vec<String>) {
-        if borrow_collected_arguments.len() == 0 { // If no arguments are passed, it is an error.
-            println!("Invalid syntax. Zero arguments were passed. Use \"--help\" or \"-h\" to see options and syntax.");
+    fn keep_options_of_the_command(passed_options: Vec<PassedOption>) -> Vec<PassedOption> { // The config file and the environment hold defaults for every command, so their options of the other commands are dropped. On the command line they are an error.
+        let passed_commands: Vec<&(&str, &str, OptionScope, &str)> = COMMAND_TABLE.iter().filter(|(_, long, _, _)| option_passed(&passed_options, long)).collect();
+
+        if passed_commands.len() > 1 { // If grep and find are both passed. It is an error because thoes commands do not work together.
+            let spellings: Vec<String> = passed_options.iter().filter(|option| COMMAND_TABLE.iter().any(|(_, long, _, _)| *long == option.long)).map(|option| option.spelling.clone()).collect();
+            print_line!("Invalid syntax. The grep (--simple-grep, -sg), find (--simple-find, -sf), replace (--simple-replace, -sr), index (--simple-index, -si) and updatedb (--simple-updatedb, -su) commands cannot be used together: {}. Those processes can only be ran one at a time. Use \"--help\" or \"-h\" to see options and syntax.", spellings.join(" "));
             process::exit(1);
         }
-    
-        if borrow_collected_arguments.len() > 20 { // If too many arguments are passed, it is an error.
-            println!("Invalid syntax. Too many arguments were passed. Use \"--help\" or \"-h\" to see options and syntax."); // TODO: Make this number more specific to what the actual max is..
+
+        let (command, command_scope): (&str, OptionScope) = match passed_commands.first() {
+            Some((command, _, scope, _)) => (command, *scope),
+            None => { return passed_options; } // Without a command only help, version and the generators run, so every option is kept.
+        };
+        let belongs_to_the_command = |option: &PassedOption| -> bool {
+            match OPTION_TABLE.iter().find(|spec| spec.long == option.long).map(|spec| spec.scope) {
+                Some(scope) => scope.applies_to(command_scope),
+                None => true,
+            }
+        };
+
+        let wrong_options: Vec<String> = passed_options.iter().filter(|option| option.source == SettingSource::CommandLine && !belongs_to_the_command(option)).map(|option| option.spelling.clone()).collect();
+        if !wrong_options.is_empty() {
+            print_line!("Invalid syntax. These options cannot be used with the {} command: {}. Use \"lozgrep help {}\" to see the options of the {} command.", command, wrong_options.join(", "), command, command);
             process::exit(1);
         }
-    }
-
-    fn verify_options_are_valid (borrow_collected_arguments: &Vec<String>, borrow_possible_options: &[&str; 14]) -> Vec<String> {
-        let filtered_options: Vec<String> = borrow_collected_arguments // Parses through all the collected arguments and pulls out any options (-- -).
-        .iter() // creates an iterator.
-        .filter(|option| option.starts_with("--") || option.starts_with("-")) // .filter(...) is used to retain only items that satisfy a given condition. |option| is a closure (anonymous function) parameter representing each item passed from the iterator. Checks if the String starts with -- or -.
-        .cloned() // Is used to convert &String (a reference) into an owned String. This is necessary because we want to create a new vector with owned String values, rather than references to the original vector’s items
-        .collect(); //  Takes the filtered and cloned items from the iterator and collects them into a new Vec<String>. This newly created vector is then assigned to filtered_options.
-        
-        let bad_options: Vec<String> = filtered_options// Parses through all the filtered options and checks if they are actual valid options.
-        .iter()
-        .filter(|option| !borrow_possible_options.contains(&option.as_str())) // Filters by options that are not contained inside of borrow_all_possible_options.
-        .cloned()
-        .collect();
 
-        if !bad_options.is_empty() { // If bad_options is not empty, then it means that bad option were passed.
-            let print_bad_options: String = bad_options.join(", "); // Turns the values of &bad_options into a string so a clear error message can be printed.
-            
-            if bad_options.len() == 1 { // Different error messages depending on the situation.
-                println!("Invalid syntax. An unknown option was passed: {}. Use \"--help\" or \"-h\" to see options and syntax.", &print_bad_options);
-                process::exit(1);
-    
-            } else {
-                println!("Invalid syntax. Unknown options were passed: {}. Use \"--help\" or \"-h\" to see options and syntax.", &print_bad_options);
-                process::exit(1);
-            }
+        if option_passed(&passed_options, "--verbose") == true {
+            for option in passed_options.iter().filter(|option| option.source == SettingSource::Environment && !belongs_to_the_command(option)) { print_line!("VERBOSE: {} is ignored, the {} command does not use it", option.spelling, command); } // LOZGREP_SIZE=+1 set for find does not stop grep.
         }
 
-        if check_for_exact_duplicate_options(&filtered_options) == true { // Function will return true if there are eacxt duplicated options (-p, -p, or --help, --help), which is an error.
-            let print_filtered_options: String = filtered_options.join(", ");
-            
-            println!("Invalid syntax. Duplicated options were passed: {}. Use \"--help\" or \"-h\" to see options and syntax.", &print_filtered_options);
+        return passed_options.into_iter().filter(|option| belongs_to_the_command(option)).collect();
+    }
+
+    fn build_help_configuration(borrow_collected_arguments: &Vec<String>) -> Options { // Returns Options with only help set, and the mode the help is for.
+        if borrow_collected_arguments.len() > 2 {
+            print_line!("Invalid syntax. The help command takes one topic at most: {}. Use \"lozgrep help\" followed by one of the commands: grep, find, replace, index or updatedb.", borrow_collected_arguments[1..].join(" "));
             process::exit(1);
         }
 
-        check_for_logically_duplicate_options(&filtered_options); // If this function finds logically duplicate options (--help -h --path -p), error will be created and process will end.
+        let help_topic: Option<OptionScope> = match borrow_collected_arguments.get(1) {
+            None => None,
+            Some(topic) => match COMMAND_TABLE.iter().find(|(command, _, _, _)| command == topic) {
+                Some((_, _, scope, _)) => Some(*scope),
+                None => {
+                    print_line!("Invalid syntax. Unknown help topic: {}. The help topics are grep, find, replace, index and updatedb. Use \"lozgrep help grep\" for the help of the grep command.", topic);
+                    process::exit(1);
+                }
+            },
+        };
 
-        return filtered_options; // If there is no issues with the passed options, then filtered_options will be returned to build_running_configuration.
+        let help_option: PassedOption = PassedOption { long: "--help", spelling: "help".to_string(), value: None, source: SettingSource::CommandLine };
+
+        return Options::build_options(&vec![help_option], "null".to_string(), Vec::new(), build_metadata_filters(&Vec::new()), build_file_type_filter(&Vec::new()), PatternMatcher::build(Vec::new()), None, help_topic);
     }
 
-    fn check_for_exact_duplicate_options<T: Eq + std::hash::Hash>(borrow_filtered_options: &[T]) -> bool { // Generics (<T>): Allows the function to operate on slices of any data type. Eq Trait: Ensures that the elements can be compared for equality. Hash Trait: Allows the elements to be hashed, which is necessary for inserting them into a HashSet.
-        if borrow_filtered_options.len() != borrow_filtered_options // if borrow_filtered_options.len() != borrow_filtered_options.iter()..collect::<HashSet<_>>().len() {}.
-        .iter() // Creates an iterator over references to the elements (&T).
-        .collect::<HashSet<_>>()// Transforms the iterator into a collection, in this case, a HashSet. Using ::<HashSet<_>> explicitly tells Rust to collect into a HashSet, and the underscore _ lets the compiler infer the specific type.
-        .len() {
-            return true; // There are repeated options.
-            
-        } else {
-            return false; // There are not repeated options.
+    fn verify_argument_length(borrow_collected_arguments: &Vec<String>) { // There is no maximum, shell globs (src/*.rs) can pass thousands of paths.
+        if borrow_collected_arguments.len() == 0 { // If no arguments are passed, it is an error.
+            print_line!("Invalid syntax. Zero arguments were passed. Use \"--help\" or \"-h\" to see options and syntax.");
+            process::exit(1);
+        }
+    }
+
+    fn tokenize_arguments(borrow_collected_arguments: &Vec<String>, borrow_source: &str) -> (Vec<PassedOption>, Vec<String>) { // Returns the options that were found, and the positional values. borrow_source is added to the error messages, so the user knows if the bad option came from the config file.
+        match split_arguments(borrow_collected_arguments, borrow_source) {
+            Ok(options_and_values) => { return options_and_values; }
+            Err(error_message) => { // Unknown options, missing values and unreadable argument files.
+                print_line!("{}", error_message);
+                process::exit(1);
+            }
         }
     }
 
-    fn check_for_logically_duplicate_options(borrow_filtered_options: &Vec<String>) {
-        let double_tack: Vec<String> = borrow_filtered_options // Creates a vector of all the options that start with --.
-        .iter() 
-        .filter(|option| option.starts_with("--")) 
-        .cloned()
-        .collect();
+    fn split_arguments(borrow_collected_arguments: &Vec<String>, borrow_source: &str) -> Result<(Vec<PassedOption>, Vec<String>), String> { // Same as tokenize_arguments, but returns the error message instead of exiting.
+        let mut passed_options: Vec<PassedOption> = Vec::new();
+        let mut positional_values: Vec<String> = Vec::new();
+        let mut bad_options: Vec<(String, Option<&'static str>)> = Vec::new(); // (the unknown option, the option it is closest to)
+        let mut argument_file_stack: Vec<(String, usize)> = Vec::new(); // The argument files that are being read, as (path, index after their last argument). Used to catch an argument file that includes itself.
+        let mut arguments: Vec<String> = borrow_collected_arguments.clone(); // A copy, because @argfile arguments are replaced by the arguments inside of the file.
+        let borrow_collected_arguments: &mut Vec<String> = &mut arguments;
+        let mut count: usize = 0;
 
-        let mut build_error_message: String = String::new(); // Creates a mutable string, text is appended to it if there is an error.
+        while count < borrow_collected_arguments.len() {
+            while argument_file_stack.last().map(|(_, end)| count >= *end) == Some(true) { argument_file_stack.pop(); } // Every argument of the file was read, so the same file can be included again: @opts @opts.
 
-        for option in double_tack { // ["--help", "-h", "--version", "-ver", "--verbose", "-v", "--query", "-q", "--path", "-p", "--simple-grep", "-sg", "--simple-find", "-sf"].
-            if option == "--help" { // If the double tacked option is present, it is an error if the single tacked option is present.
-                if borrow_filtered_options.contains(&"-h".to_string()) {
-                    build_error_message.push_str("--help -h ");
-                }
+            let argument: &String = &borrow_collected_arguments[count].clone();
+            count += 1;
 
-            } else if option =="--version" {
-                if borrow_filtered_options.contains(&"-ver".to_string()) {
-                    build_error_message.push_str("--version -ver ");
+            if argument.starts_with("@") && argument.len() > 1 && Path::new(&argument[1..]).is_file() { // @file is replaced by the arguments in the file, one per line. They are read next, as if they were typed in place of @file. If there is no such file, @Override is a value like any other.
+                let file_path: &str = &argument[1..];
+                if argument_file_stack.iter().any(|(path, _)| path == file_path) {
+                    return Err(format!("Invalid syntax. The argument file @{} includes itself. Use \"--help\" or \"-h\" to see options and syntax.", file_path));
                 }
 
-            } else if option =="--verbose" {
-                if borrow_filtered_options.contains(&"-v".to_string()) {
-                    build_error_message.push_str("--verbose -v ");
-                }
+                let file_arguments: Vec<String> = read_argument_file(file_path)?;
+                let file_length: usize = file_arguments.len();
+                borrow_collected_arguments.splice(count - 1..count, file_arguments);
+                count -= 1;
+                for (_, end) in argument_file_stack.iter_mut() { *end = *end + file_length - 1; } // The files that are still being read now end later, or earlier if the file was empty.
+                argument_file_stack.push((file_path.to_string(), count + file_length));
 
-            } else if option =="--query" {
-                if borrow_filtered_options.contains(&"-q".to_string()) {
-                    build_error_message.push_str("--query -q ");
-                }
+            } else if argument == "--" { // Everything after -- is a positional value, even if it starts with -.
+                positional_values.extend(borrow_collected_arguments[count..].iter().cloned());
+                break;
 
-            } else if option =="--path" {
-                if borrow_filtered_options.contains(&"-p".to_string()) {
-                    build_error_message.push_str("--path -p ");
-                }
+            } else if argument.starts_with("--") { // --name or --name=value. The name before = has to be an option of the table.
+                let (name, attached_value) = match argument.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (argument.as_str(), None),
+                };
+
+                match OPTION_TABLE.iter().find(|spec| spec.long == name) {
+                    Some(spec) => {
+                        if spec.takes_value == false && attached_value.is_some() {
+                            return Err(format!("Invalid syntax. The {} option does not take a value: {}. Use \"--help\" or \"-h\" to see options and syntax.", name, argument));
+                        }
 
-            } else if option =="--simple-grep" {
-                if borrow_filtered_options.contains(&"-sg".to_string()) {
-                    build_error_message.push_str("--simple-grep -sg ");
+                        let value: Option<String> = if spec.takes_value == true && attached_value.is_none() { Some(take_next_value(borrow_collected_arguments, &mut count, name)?) } else { attached_value };
+                        passed_options.push(PassedOption { long: spec.long, spelling: name.to_string(), value, source: SettingSource::CommandLine });
+                    }
+                    None => { bad_options.push((argument.clone(), suggest_option(argument, &[]))); }
                 }
 
-            } else if option =="--simple-find" {
-                if borrow_filtered_options.contains(&"-sf".to_string()) {
-                    build_error_message.push_str("--simple-find -sf ");
+            } else if argument.starts_with("-") && argument.len() > 1 { // -name, -namevalue or bundled single letter flags (-vh). A lone - is a positional value.
+                if let Some(spec) = OPTION_TABLE.iter().find(|spec| spec.short == argument) { // Exact short option (-sg, -ver, -q).
+                    let value: Option<String> = if spec.takes_value == true { Some(take_next_value(borrow_collected_arguments, &mut count, argument)?) } else { None };
+                    passed_options.push(PassedOption { long: spec.long, spelling: argument.clone(), value, source: SettingSource::CommandLine });
+
+                } else if let Some(spec) = OPTION_TABLE.iter().filter(|spec| spec.takes_value == true && argument.starts_with(spec.short)).max_by_key(|spec| spec.short.len()) { // Value stuck to the short option (-qfoo, -sz+10M). The longest short option wins, so -tnjson is -tn json and not -t njson.
+                    passed_options.push(PassedOption { long: spec.long, spelling: spec.short.to_string(), value: Some(argument[spec.short.len()..].to_string()), source: SettingSource::CommandLine }); // The rest of the argument is always the value, even if it looks like another option: -qtype is -q type.
+
+                } else if let Some(bundled_options) = split_bundled_flags(argument, borrow_collected_arguments, &mut count)? { // -vh is -v -h. The last letter can take a value (-vq foo, -vqfoo).
+                    passed_options.extend(bundled_options);
+
+                } else {
+                    bad_options.push((argument.clone(), suggest_option(argument, &[])));
                 }
+
+            } else {
+                positional_values.push(argument.clone());
             }
         }
 
-        if !build_error_message.is_empty() { // If the build_error_message string is not empty, that means there are doubled options, and therefore is an error.
-            let error_message = format!("Invalid syntax. Duplicate options were passed: {}. Use \"--help\" or \"-h\" to see options and syntax.", build_error_message);
-            println!("{}", error_message);
-            process::exit(1);
+        if !bad_options.is_empty() { // If bad_options is not empty, then it means that bad option were passed.
+            let print_bad_options: String = bad_options.iter().map(|(bad_option, suggestion)| match suggestion { // Every unknown option is listed, with the closest known option if there is one: --follw (did you mean --follow?)
+                Some(suggestion) => format!("{} (did you mean {}?)", bad_option, suggestion),
+                None => bad_option.clone(),
+            }).collect::<Vec<String>>().join(", "); // Turns the values of &bad_options into a string so a clear error message can be printed.
+
+            if bad_options.len() == 1 { // Different error messages depending on the situation.
+                return Err(format!("Invalid syntax. An unknown option was passed{}: {}. Use \"--help\" or \"-h\" to see options and syntax.", borrow_source, &print_bad_options));
+
+            } else {
+                return Err(format!("Invalid syntax. Unknown options were passed{}: {}. Use \"--help\" or \"-h\" to see options and syntax.", borrow_source, &print_bad_options));
+            }
         }
+
+        return Ok((passed_options, positional_values));
     }
 
-    fn verify_values_are_valid(borrow_collected_arguments: &Vec<String>, borrow_validated_options: &Vec<String>) -> Vec<String>{ // Filters and collects everything else besides the options.
-        let filtered_values: Vec<String> = borrow_collected_arguments
-        .iter()
-        .filter(|value| !value.starts_with("--") && !value.starts_with("-"))// Will filter out all other passed arguments that are not options (--, -).
-        .cloned()
-        .collect();
+    fn suggest_option(borrow_bad_option: &str, borrow_skipped_options: &[&str]) -> Option<&'static str> { // Returns the long or short option that is closest to the unknown option, None if nothing is close enough to be a typo. The options in borrow_skipped_options are never suggested.
+        let name: &str = borrow_bad_option.split_once('=').map(|(name, _)| name).unwrap_or(borrow_bad_option);
+        let letters: usize = name.trim_start_matches('-').chars().count();
+        let allowed_distance: usize = if letters < 2 { 0 } else { std::cmp::max(1, letters / 3) }; // One typo per three letters. -x is not close to anything, every single letter option is one edit away.
 
-        let query_present: bool = borrow_validated_options.contains(&"--query".to_string()) || borrow_validated_options.contains(&"-q".to_string()); // query and path_present will == true if they contain query or path options. 
-        let path_present: bool = borrow_validated_options.contains(&"--path".to_string()) || borrow_validated_options.contains(&"-p".to_string());
+        let (distance, suggestion) = OPTION_TABLE.iter().flat_map(|spec| [spec.long, spec.short]).filter(|candidate| !borrow_skipped_options.contains(candidate)).map(|candidate| (edit_distance(name, candidate), candidate)).min_by_key(|(distance, _)| *distance)?; // min_by_key keeps the first of equal distances, so the table order breaks ties.
 
-        if filtered_values.len() == 0 && query_present == true || filtered_values.len() == 0 && path_present == true { // Since query and path require values, it is an error if there are xero values and query or path is present.
-            if query_present == true && path_present == true { // Different error messages depending on the situation.
-                println!("Invalid syntax. The query (--query, -q) and path (--path, -p) options require a non-option value to follow it. Use \"--help\" or \"-h\" to see options and syntax.");
-                process::exit(1);
-    
-            } else if query_present == true {
-                println!("Invalid syntax. The query (--query, -q) option requires a non-option value to follow it. Use \"--help\" or \"-h\" to see options and syntax.");
-                process::exit(1);
-    
-            } else if path_present == true {
-                println!("Invalid syntax. The path (--path, -p) option requires a non-option value to follow it. Use \"--help\" or \"-h\" to see options and syntax.");
-                process::exit(1);
+        if distance <= allowed_distance { return Some(suggestion); }
+        return None;
+    }
+
+    fn edit_distance(borrow_first: &str, borrow_second: &str) -> usize { // Number of inserted, deleted, replaced or swapped characters to turn one string into the other. Swapped neighbours count as one edit, because --fllow and --folow are the typos people make.
+        let first: Vec<char> = borrow_first.chars().collect();
+        let second: Vec<char> = borrow_second.chars().collect();
+        let mut distances: Vec<Vec<usize>> = vec![vec![0; second.len() + 1]; first.len() + 1]; // distances[i][j] is the distance between the first i characters of first and the first j characters of second.
+
+        for (i, row) in distances.iter_mut().enumerate() { row[0] = i; }
+        for (j, cell) in distances[0].iter_mut().enumerate() { *cell = j; }
+
+        for i in 1..=first.len() {
+            for j in 1..=second.len() {
+                let replace_cost: usize = if first[i - 1] == second[j - 1] { 0 } else { 1 };
+                distances[i][j] = (distances[i - 1][j] + 1).min(distances[i][j - 1] + 1).min(distances[i - 1][j - 1] + replace_cost);
+
+                if i > 1 && j > 1 && first[i - 1] == second[j - 2] && first[i - 2] == second[j - 1] {
+                    distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+                }
             }
         }
 
-        if filtered_values.len() == 1 || filtered_values.len() > 2 { // There should only be two non-option arguments, one for query, one for path.
-            if filtered_values.len() == 1 { // Different error messages depending on the situation.
-                let print_bad_arguments: String = filtered_values.join(" ");
-                println!("Invalid syntax. Too few non-option values were passed: {}. Use \"--help\" or \"-h\" to see options and syntax.", print_bad_arguments);
-                process::exit(1);
-    
-            } else { // filtered_argument.len() > 2.
-                let print_bad_arguments: String = filtered_values.join(", ");
-                println!("Invalid syntax. Too many non-option values were passed: {}. Use \"--help\" or \"-h\" to see options and syntax.", print_bad_arguments);
-                process::exit(1);
+        return distances[first.len()][second.len()];
+    }
+
+    fn read_argument_file(borrow_file_path: &str) -> Result<Vec<String>, String> { // Every line is one argument, spaces are kept. Empty lines are skipped.
+        let file_contents: String = match fs::read_to_string(borrow_file_path) {
+            Ok(file_contents) => file_contents,
+            Err(error_one) => { return Err(format!("Invalid syntax. Problem reading the argument file @{}: {}. Use \"--help\" or \"-h\" to see options and syntax.", borrow_file_path, error_one)); }
+        };
+
+        return Ok(file_contents.lines().map(|line| line.trim_end_matches('\r')).filter(|line| !line.is_empty()).map(|line| line.to_string()).collect());
+    }
+
+    fn take_next_value(borrow_collected_arguments: &Vec<String>, borrow_count: &mut usize, borrow_option_name: &str) -> Result<String, String> { // The argument after an option that takes a value always belongs to it, even if it starts with -.
+        if *borrow_count >= borrow_collected_arguments.len() {
+            return Err(format!("Invalid syntax. The {} option requires a value to follow it. Use \"--help\" or \"-h\" to see options and syntax.", borrow_option_name));
+        }
+
+        let value: String = borrow_collected_arguments[*borrow_count].clone();
+        *borrow_count += 1; // Skips over the value, so it is not read as an option or positional value.
+
+        return Ok(value);
+    }
+
+    fn split_bundled_flags(borrow_argument: &String, borrow_collected_arguments: &Vec<String>, borrow_count: &mut usize) -> Result<Option<Vec<PassedOption>>, String> { // Returns None if one of the letters is not a single letter option, so the whole argument is reported as unknown.
+        let mut bundled_options: Vec<PassedOption> = Vec::new();
+        let letters: Vec<char> = borrow_argument.chars().skip(1).collect();
+
+        for (index, letter) in letters.iter().enumerate() {
+            let short: String = format!("-{}", letter);
+            let spec: &OptionSpec = match OPTION_TABLE.iter().find(|spec| spec.short == short) {
+                Some(spec) => spec,
+                None => { return Ok(None); }
+            };
+
+            if spec.takes_value == true { // The rest of the argument is the value. If there is no rest, the next argument is.
+                let rest: String = letters[index + 1..].iter().collect();
+                let value: String = if rest.is_empty() { take_next_value(borrow_collected_arguments, borrow_count, &short)? } else { rest };
+                bundled_options.push(PassedOption { long: spec.long, spelling: short, value: Some(value), source: SettingSource::CommandLine });
+                return Ok(Some(bundled_options));
             }
+
+            bundled_options.push(PassedOption { long: spec.long, spelling: short, value: None, source: SettingSource::CommandLine });
         }
 
-        return filtered_values;
+        return Ok(Some(bundled_options));
     }
 
-    fn parse_path_and_query(borrow_collected_arguments: &Vec<String>, borrow_validated_values: &Vec<String>) -> (String, String) { // Returns a tuple of query and path.
-        let mut count: usize = 0;
-        let mut query: String = String::new(); // Mutable empty string.
-        let mut path: String = String::new();
-        let mut error_occurred: usize = 0;
+    fn check_for_duplicate_options(borrow_passed_options: &Vec<PassedOption>, borrow_source: &str) { // Exact duplicates (-p -p) and logical duplicates (--path -p) are both the same long name at this point.
+        let mut seen_options: HashSet<&str> = HashSet::new();
+        let mut duplicated_options: Vec<String> = Vec::new();
+
+        for option in borrow_passed_options {
+            if option.long == "--path" { continue; } // --path can be passed more than once, every value is another path to search.
+
+            if seen_options.insert(option.long) == false { // insert returns false if the long name was already in the set.
+                let spellings: Vec<String> = borrow_passed_options.iter().filter(|other| other.long == option.long).map(|other| other.spelling.clone()).collect();
+                let print_spellings: String = spellings.join(" ");
+
+                if !duplicated_options.contains(&print_spellings) { duplicated_options.push(print_spellings); }
+            }
+        }
 
-        if borrow_validated_values.contains(&borrow_collected_arguments[0]) { // If a non-option value is the first argument passed, it is an error because that has no meaning. An option has to come first.
-            println!("Invalid syntax. An option has to be the first argument passed. Use \"--help\" or \"-h\" to see options and syntax.");
+        if !duplicated_options.is_empty() {
+            print_line!("Invalid syntax. Duplicate options were passed{}: {}. Use \"--help\" or \"-h\" to see options and syntax.", borrow_source, duplicated_options.join(", "));
             process::exit(1);
         }
+    }
 
-        while count < borrow_collected_arguments.len() { // This loop is structured like this for a reason. Logic errors were occuring when done the other way.
-            if borrow_collected_arguments[count] == borrow_validated_values[0] { // If the current value of collected_arguments equals a filtered_values. 
-                if borrow_collected_arguments[count -1] == "--query" || borrow_collected_arguments[count -1] == "-q" { // Look at the option that comes before the current value. If the option that comes before is query or path, you know that the current value is either the query or the path.
-                    query = borrow_collected_arguments[count].clone(); // If the found value meets the parameters, update the string.
+    fn positional_values_to_options(borrow_command_line_options: &Vec<PassedOption>, positional_values: Vec<String>) -> Vec<PassedOption> { // The first positional value is the query if --query or --patterns-file was not passed, like grep PATTERN FILE... and grep -f FILE FILE... replace takes the replacement next, if --replacement was not passed. Every other positional value is a path.
+        let mut positional_options: Vec<PassedOption> = Vec::new();
+        let mut query_taken: bool = option_passed(borrow_command_line_options, "--query") || option_passed(borrow_command_line_options, "--patterns-file") || option_passed(borrow_command_line_options, "--simple-index") || option_passed(borrow_command_line_options, "--simple-updatedb"); // index and updatedb only take directories.
+        let mut replacement_taken: bool = option_passed(borrow_command_line_options, "--simple-replace") == false || option_passed(borrow_command_line_options, "--replacement");
 
-                } else if borrow_collected_arguments[count -1] == "--path" || borrow_collected_arguments[count -1] == "-p" {
-                    path = borrow_collected_arguments[count].clone();
+        for value in positional_values {
+            let long: &'static str = if query_taken == false { "--query" } else if replacement_taken == false { "--replacement" } else { "--path" };
+            if query_taken == true { replacement_taken = true; }
+            query_taken = true;
+            positional_options.push(PassedOption { long, spelling: value.clone(), value: Some(value), source: SettingSource::CommandLine });
+        }
 
-                } else {
-                    error_occurred = error_occurred + 1; // If query or path cannot be assigned, increment the error counter.
+        return positional_options;
+    }
+
+    fn read_environment_options() -> (Vec<PassedOption>, Vec<&'static str>) { // Returns the options set by LOZGREP_* variables, and the flags that were turned off with 0, false, no or off.
+        let mut environment_options: Vec<PassedOption> = Vec::new();
+        let mut disabled_flags: Vec<&'static str> = Vec::new();
+
+        for spec in OPTION_TABLE.iter().filter(|spec| spec.long != "--help" && spec.long != "--version" && spec.long != "--generate-man-page" && spec.long != "--generate-completions") { // Help, version and the generators only make sense when they are asked for.
+            let variable_name: String = environment_variable_name(spec.long);
+
+            let value: String = match env::var(&variable_name) {
+                Ok(value) if !value.is_empty() => value, // An empty variable is the same as an unset one.
+                Ok(_) | Err(env::VarError::NotPresent) => { continue; }
+                Err(env::VarError::NotUnicode(_)) => {
+                    print_line!("Environment error. The {} variable is not valid unicode.", variable_name);
+                    process::exit(1);
                 }
-            }
-            
-            if borrow_collected_arguments[count] == borrow_validated_values [1] {
-                if borrow_collected_arguments[count -1] == "--query" || borrow_collected_arguments[count -1] == "-q" {
-                    query = borrow_collected_arguments[count].clone();
+            };
 
-                } else if borrow_collected_arguments[count -1] == "--path" || borrow_collected_arguments[count -1] == "-p" {
-                    path = borrow_collected_arguments[count].clone();
+            if spec.takes_value == true {
+                environment_options.push(PassedOption { long: spec.long, spelling: variable_name, value: Some(value), source: SettingSource::Environment });
 
-                } else {
-                    error_occurred = error_occurred + 1;
+            } else {
+                match value.to_lowercase().as_str() {
+                    "1" | "true" | "yes" | "on" => { environment_options.push(PassedOption { long: spec.long, spelling: variable_name, value: None, source: SettingSource::Environment }); }
+                    "0" | "false" | "no" | "off" => { disabled_flags.push(spec.long); }
+                    _ => {
+                        print_line!("Environment error. The {} variable has to be 1, true, yes, on, 0, false, no or off: {}", variable_name, value);
+                        process::exit(1);
+                    }
                 }
             }
+        }
 
-            count += 1;
+        return (environment_options, disabled_flags);
+    }
+
+    pub fn environment_variable_name(borrow_long: &str) -> String { // --type-not becomes LOZGREP_TYPE_NOT.
+        return format!("LOZGREP_{}", borrow_long.trim_start_matches('-').to_uppercase().replace('-', "_"));
+    }
+
+    fn layer_options(lower_options: Vec<PassedOption>, upper_options: Vec<PassedOption>) -> Vec<PassedOption> { // An option in the upper layer replaces the same option in the lower layer.
+        let exclusive_groups: [&[&str]; 2] = [&["--simple-grep", "--simple-find", "--simple-replace", "--simple-index", "--simple-updatedb"], &["--quiet-errors", "--error-summary", "--list-errors"]]; // Options in a group cannot be used together, so passing one of them in the upper layer replaces all of them from the lower layer.
+        let mut layered_options: Vec<PassedOption> = Vec::new();
+
+        for option in lower_options {
+            let replaced_by_upper: bool = option_passed(&upper_options, option.long) || exclusive_groups.iter().any(|group| group.contains(&option.long) && group.iter().any(|member| option_passed(&upper_options, member)));
+
+            if replaced_by_upper == false { layered_options.push(option); }
         }
 
-        if error_occurred != 0 {
-            if query.is_empty() && path.is_empty() {
-                println!("Invalid syntax. The query option (--query, -q) and path option (--path, -p) are not followed by a non-option value. Use \"--help\" or \"-h\" to see options and syntax.");
-                process::exit(1);
+        layered_options.extend(upper_options);
 
-            } else if query.is_empty() {
-                println!("Invalid syntax. The query option (--query, -q) is not followed by a non-option value. Use \"--help\" or \"-h\" to see options and syntax.");
-                process::exit(1);
+        return layered_options;
+    }
+
+    fn read_config_file_options() -> (Vec<PassedOption>, Option<String>) { // Returns the options in the config file, and the path of the config file that was used.
+        let config_path: String = match find_config_file() {
+            Some(config_path) => config_path,
+            None => { return (Vec::new(), None); } // No config file, nothing to read.
+        };
 
-            } else { // path.is_empty.
-                println!("Invalid syntax. The path option (--path, -p) is not followed by a non-option value. Use \"--help\" or \"-h\" to see options and syntax.");
+        let file_contents: String = match fs::read_to_string(&config_path) {
+            Ok(file_contents) => file_contents,
+            Err(error_one) => {
+                print_line!("Config error. Problem reading the config file {}: {}. Use \"--no-config\" or \"-nc\" to ignore it.", config_path, error_one);
                 process::exit(1);
             }
-        }
+        };
+
+        let config_arguments: Vec<String> = file_contents.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#')).map(|line| line.to_string()).collect(); // Every line is one argument, like an @argfile. Lines that start with # are comments.
+        let source: String = format!(" in the config file {}", config_path);
+        let (mut config_options, config_values) = tokenize_arguments(&config_arguments, &source);
+        check_for_duplicate_options(&config_options, &source);
 
-        if validate_path(&path) == false { // If validate_path returns false, process is exited.
+        if !config_values.is_empty() { // A value has to belong to an option, the config file cannot pass a query or path on its own.
+            print_line!("Config error. The config file {} can only hold options, these values do not follow an option: {}. Use \"--no-config\" or \"-nc\" to ignore it.", config_path, config_values.join(", "));
             process::exit(1);
         }
 
-        if query.starts_with("/") { // The escape character for queries is /, therefore if query starts with /, it must be removed. And if the user wants to search for / they have to type //.
+        for option in &mut config_options { option.source = SettingSource::ConfigFile; }
+
+        return (config_options, Some(config_path));
+    }
+
+    fn find_config_file() -> Option<String> { // LOZGREP_CONFIG, then $XDG_CONFIG_HOME/lozgrep/config, then $HOME/.config/lozgrep/config.
+        if let Ok(config_path) = env::var("LOZGREP_CONFIG") { // If the variable is set, the file has to exist, so a typo is not silently ignored.
+            if !config_path.is_empty() { return Some(config_path); }
+        }
+
+        let config_directory: String = match env::var("XDG_CONFIG_HOME") {
+            Ok(directory) if !directory.is_empty() => directory,
+            _ => match env::var("HOME") {
+                Ok(home) if !home.is_empty() => format!("{}/.config", home),
+                _ => { return None; }
+            },
+        };
+
+        let config_path: String = format!("{}/lozgrep/config", config_directory);
+
+        if Path::new(&config_path).is_file() { return Some(config_path); } else { return None; } // The default config file is optional.
+    }
+
+    fn assign_query_and_paths(borrow_passed_options: &Vec<PassedOption>) -> (String, Vec<String>) { // Returns a tuple of query and paths. "null" means the query was not passed, an empty vector means no path was passed.
+        let mut query: String = option_value(borrow_passed_options, "--query").unwrap_or_else(|| "null".to_string());
+        let paths: Vec<String> = borrow_passed_options.iter().filter(|option| option.long == "--path").filter_map(|option| option.value.clone()).collect();
+
+        for path in &paths {
+            if validate_path(path) == false { // If validate_path returns false, process is exited.
+                process::exit(1);
+            }
+        }
+
+        if query != "null" && query.starts_with("/") { // The escape character for queries is /, therefore if query starts with /, it must be removed. And if the user wants to search for / they have to type //. It is not needed anymore, but older scripts still use it.
             query = query.chars().skip(1).collect::<String>(); // Since query is mutable, just re-define it with the first char trimmed.
         }
 
-        return (query, path);
+        return (query, paths);
+    }
+
+    fn option_value(borrow_passed_options: &Vec<PassedOption>, borrow_long: &str) -> Option<String> { // Returns the value of an option, or None if the option was not passed.
+        return borrow_passed_options.iter().find(|option| option.long == borrow_long).and_then(|option| option.value.clone());
+    }
+
+    fn option_passed(borrow_passed_options: &Vec<PassedOption>, borrow_long: &str) -> bool {
+        return borrow_passed_options.iter().any(|option| option.long == borrow_long);
     }
 
-    fn validate_path(borrow_path: &String) -> bool { // Checks if the given path is valid, if not valid creates errors.
+    fn validate_path(borrow_path: &String) -> bool { // Checks if the given path is valid, if not valid creates errors. The path is printed, because there can be many of them.
         match fs::metadata(borrow_path) { // Attempts to retrieve metadata about the file or directory. match Statement: Matches the result of fs::metadata(borrow_path) to handle both success and error cases.
-            Ok(_) => { return true; } // Was able to retrieve metadata, therfore the path is valid, so true is returned. 
+            Ok(_) => { return true; } // Was able to retrieve metadata, therfore the path is valid, so true is returned.
             Err(error) => match error.kind() { // Nested match error.kind(): Matches on the specific kind of I/O error to determine why the metadata retrieval failed.
                 io::ErrorKind::NotFound => { // Path does not exist.
-                    println!("Path error. The path given is not valid because the path cannot be found.");
+                    print_line!("Path error. The path given is not valid because the path cannot be found: {}", borrow_path);
                     return false;
 
                 } io::ErrorKind::PermissionDenied => { // Current user doesn't have proper permissions.
-                    println!("Path error. The path given is not valid because permission was denied.");
+                    print_line!("Path error. The path given is not valid because permission was denied: {}", borrow_path);
                     return false;
 
                 } _ => { // Wildcard to catch everything else.
-                    println!("Path error. The lozgrep cannot access the specified path"); 
+                    print_line!("Path error. The lozgrep cannot access the specified path: {}", borrow_path);
                     return false;
                 }
             }
-        } 
+        }
+    }
+
+    fn check_if_the_given_options_work_together(borrow_passed_options: &Vec<PassedOption>, borrow_valid_query: &String, borrow_valid_paths: &Vec<String>) { // keep_options_of_the_command already made sure there is one command at most.
+        let simple_grep: bool = option_passed(borrow_passed_options, "--simple-grep");
+        let simple_find: bool = option_passed(borrow_passed_options, "--simple-find");
+        let simple_replace: bool = option_passed(borrow_passed_options, "--simple-replace");
+        let simple_index: bool = option_passed(borrow_passed_options, "--simple-index");
+        let simple_updatedb: bool = option_passed(borrow_passed_options, "--simple-updatedb");
+
+        if borrow_valid_paths.is_empty() && simple_index == true && option_passed(borrow_passed_options, "--help") == false {
+            print_line!("Invalid syntax. The index (--simple-index, -si) command needs a directory (--path, -p) to index: lozgrep index DIRECTORY. Use \"lozgrep help index\" to see the options of the index command.");
+            process::exit(1);
+        }
+
+        if borrow_valid_paths.is_empty() && simple_updatedb == true && option_passed(borrow_passed_options, "--help") == false {
+            print_line!("Invalid syntax. The updatedb (--simple-updatedb, -su) command needs a directory (--path, -p) to save: lozgrep updatedb DIRECTORY. Use \"lozgrep help updatedb\" to see the options of the updatedb command.");
+            process::exit(1);
+        }
+
+        if ((borrow_valid_query == "null" && option_passed(borrow_passed_options, "--patterns-file") == false) || borrow_valid_paths.is_empty()) && (simple_grep == true || simple_find == true || simple_replace == true) && option_passed(borrow_passed_options, "--help") == false { // If the user does not pass a query or patterns file and a path, the grep, find and replace processes cannot run. lozgrep grep --help only asks for the help of grep.
+            print_line!("Invalid syntax. The grep (--simple-grep, -sg), find (--simple-find, -sf) and replace (--simple-replace, -sr) commands cannot be used if a query (--query, -q) or patterns file (--patterns-file, -f) and a path (--path, -p) are not passed. Use \"--help\" or \"-h\" to see options and syntax.");
+            process::exit(1);
+        }
+
+        if let Some(shell) = option_value(borrow_passed_options, "--generate-completions") { // The completion scripts are only written for these shells.
+            if !["bash", "zsh", "fish"].contains(&shell.as_str()) {
+                print_line!("Invalid syntax. The generate-completions (--generate-completions, -gc) value has to be bash, zsh or fish: {}. Use \"--help\" or \"-h\" to see options and syntax.", shell);
+                process::exit(1);
+            }
+        }
+
+        if simple_grep == true && option_passed(borrow_passed_options, "--watch") == true { check_watch_options(borrow_passed_options); }
+        if option_passed(borrow_passed_options, "--output") == true && option_passed(borrow_passed_options, "--regex") == false { // A literal query has no capture groups to fill the template with.
+            print_line!("Invalid syntax. The output (--output, -ot) option fills the template from the capture groups of a regular expression, so it needs the regex (--regex, -re) option: lozgrep grep -re 'id=(\\d+)' -ot '$1' FILE. Use \"lozgrep help grep\" to see the options of the grep command.");
+            process::exit(1);
+        }
+        if simple_grep == true && option_passed(borrow_passed_options, "--indexed") == false && option_passed(borrow_passed_options, "--watch") == false { check_file_paths(borrow_valid_paths, "grep (--simple-grep, -sg)", "grep"); }
+        if simple_grep == true && option_passed(borrow_passed_options, "--indexed") == true { check_directory_paths(borrow_valid_paths, "grep (--simple-grep, -sg) with indexed (--indexed, -ix)", "grep"); }
+        if simple_grep == true && option_passed(borrow_passed_options, "--indexed") == true && (option_passed(borrow_passed_options, "--search-zip") == true || option_passed(borrow_passed_options, "--search-archives") == true) { // The index only holds the trigrams of plain text files, compressed files and archives would never be candidates.
+            print_line!("Invalid syntax. The indexed (--indexed, -ix) option cannot be used with the search-zip (--search-zip, -z) or search-archives (--search-archives, -ar) options, the index only covers plain text files. Use \"lozgrep help grep\" to see the options of the grep command.");
+            process::exit(1);
+        }
+        if simple_index == true { check_directory_paths(borrow_valid_paths, "index (--simple-index, -si)", "index"); }
+        if simple_updatedb == true { check_directory_paths(borrow_valid_paths, "updatedb (--simple-updatedb, -su)", "updatedb"); }
+        if option_passed(borrow_passed_options, "--search-zip") == true && cfg!(feature = "search-zip") == false { // The decoders are left out of builds with --no-default-features.
+            print_line!("Invalid syntax. This lozgrep was built without the search-zip cargo feature, so search-zip (--search-zip, -z) cannot be used. Rebuild it with: cargo build --features search-zip");
+            process::exit(1);
+        }
+        if option_passed(borrow_passed_options, "--search-archives") == true && cfg!(feature = "search-archives") == false {
+            print_line!("Invalid syntax. This lozgrep was built without the search-archives cargo feature, so search-archives (--search-archives, -ar) cannot be used. Rebuild it with: cargo build --features search-archives");
+            process::exit(1);
+        }
+        if simple_find == true { check_find_options(borrow_passed_options, borrow_valid_paths); }
+        if simple_replace == true && option_passed(borrow_passed_options, "--help") == false { check_replace_options(borrow_passed_options, borrow_valid_paths); }
     }
 
-    fn check_if_the_given_options_work_together(borrow_validated_options: &Vec<String>, borrow_valid_query: &String, borrow_valid_path: &String) { // ["--help", "-h", "--version", "-ver", "--verbose", "-v", "--query", "-q", "--path", "-p", "--simple-grep", "-sg", "--simple-find", "-sf"] all the options for reference.
-        if (borrow_validated_options.contains(&"--simple-grep".to_string()) || borrow_validated_options.contains(&"-sg".to_string())) && (borrow_validated_options.contains(&"--simple-find".to_string()) || borrow_validated_options.contains(&"-sf".to_string())) { // If simple-grep and simple-find are both passed. It is an error because thoes two options do not work together.
-            println!("Invalid syntax. The simple-grep (--simple-grep, -sg) and simple-find (--simple-find, -sf) options cannot be used together. Those processes can only be ran one at a time. Use \"--help\" or \"-h\" to see options and syntax.");
+    fn check_file_paths(borrow_valid_paths: &Vec<String>, borrow_command_name: &str, borrow_command: &str) { // grep and replace work on the contents of files.
+        let directories: Vec<String> = borrow_valid_paths.iter().filter(|path| Path::new(path).is_dir()).cloned().collect();
+
+        if !directories.is_empty() { // If a path is a directory, error.
+            print_line!("Invalid syntax. When using {} the path specified needs to be a file. {} works on the contents of files. These paths are directories: {}. Use \"lozgrep help {}\" to see the options of the {} command.", borrow_command_name, borrow_command, directories.join(", "), borrow_command, borrow_command);
+            process::exit(1);
+        }
+    }
+
+    fn check_watch_options(borrow_passed_options: &Vec<PassedOption>) { // watch takes files and directories, so the paths are not checked.
+        if cfg!(feature = "watch") == false {
+            print_line!("Invalid syntax. This lozgrep was built without the watch cargo feature, so watch (--watch, -wa) cannot be used. Rebuild it with: cargo build --features watch");
+            process::exit(1);
+        }
+
+        if option_passed(borrow_passed_options, "--indexed") == true { // The files that are watched are read as they change, the index would only be in the way.
+            print_line!("Invalid syntax. The watch (--watch, -wa) option cannot be used with the indexed (--indexed, -ix) option. Use \"lozgrep help grep\" to see the options of the grep command.");
             process::exit(1);
         }
 
-        if (borrow_valid_query == "null" && borrow_valid_path == "null") && ((borrow_validated_options.contains(&"--simple-grep".to_string()) || borrow_validated_options.contains(&"-sg".to_string())) || (borrow_validated_options.contains(&"--simple-find".to_string()) || borrow_validated_options.contains(&"-sf".to_string()))) { // If the user does not pass a query and path, the simple-grep and simple-find processes cannot run.
-            println!("Invalid syntax. The simple-grep (--simple-grep, -sg) and simple-find (--simple-find, -sf) options cannot be used if a query (--query, -q) and path (--path, -p) are not passed. Use \"--help\" or \"-h\" to see options and syntax.");
+        if option_passed(borrow_passed_options, "--max-count") == true { // A followed file never ends, so there is no file to stop reading. --limit stops the whole watch instead.
+            print_line!("Invalid syntax. The watch (--watch, -wa) option cannot be used with the max-count (--max-count, -mc) option. Use --limit to stop after a number of matches. Use \"lozgrep help grep\" to see the options of the grep command.");
             process::exit(1);
-        }    
+        }
 
-        if borrow_validated_options.contains(&"--simple-grep".to_string()) || borrow_validated_options.contains(&"-sg".to_string()) { // If simple-grep is passed, the path must be a file.
-            let check_path: &Path = Path::new(borrow_valid_path);
+        if option_passed(borrow_passed_options, "--multiline") == true { // A followed file is read in pieces, so a match could be cut in two and the line numbers would start again with every piece.
+            print_line!("Invalid syntax. The watch (--watch, -wa) option cannot be used with the multiline (--multiline, -ml) option. Use \"lozgrep help grep\" to see the options of the grep command.");
+            process::exit(1);
+        }
+    }
 
-            if check_path.is_dir() { // If path is a directory, error.
-                println!("Invalid syntax. When using simple-grep (--simple-grep, -sg) the path specified needs to be a file. simple-grep searches the contents of files. Use \"--help\" or \"-h\" to see options and syntax.");
+    fn parse_count(borrow_value: &String, borrow_option_name: &str) -> usize { // max-count and limit. Exits the process if the value is not a whole number above 0.
+        match borrow_value.parse::<usize>() {
+            Ok(count) if count > 0 => { return count; }
+            _ => {
+                print_line!("Invalid syntax. The {} value has to be a whole number above 0: {}. Use \"--help\" or \"-h\" to see options and syntax.", borrow_option_name, borrow_value);
                 process::exit(1);
             }
-        } 
+        }
+    }
+
+    fn check_replace_options(borrow_passed_options: &Vec<PassedOption>, borrow_valid_paths: &Vec<String>) {
+        if option_passed(borrow_passed_options, "--replacement") == false { // Without a replacement there is nothing to replace the matches with. An empty replacement deletes the matches, so "" is allowed.
+            print_line!("Invalid syntax. The replace (--simple-replace, -sr) command needs a replacement (--replacement, -rw): lozgrep replace old new file. Use \"lozgrep help replace\" to see the options of the replace command.");
+            process::exit(1);
+        }
+
+        check_file_paths(borrow_valid_paths, "replace (--simple-replace, -sr)", "replace");
+    }
 
-        if borrow_validated_options.contains(&"--simple-find".to_string()) || borrow_validated_options.contains(&"-sf".to_string()) {
-            let check_path: &Path = Path::new(borrow_valid_path);
+    fn check_find_options(borrow_passed_options: &Vec<PassedOption>, borrow_valid_paths: &Vec<String>) {
+        if option_passed(borrow_passed_options, "--quiet-errors") == true && (option_passed(borrow_passed_options, "--error-summary") == true || option_passed(borrow_passed_options, "--list-errors") == true) { // quiet-errors hides the errors completely, so there would be nothing to summarize or list.
+            print_line!("Invalid syntax. The quiet-errors (--quiet-errors, -qe) option cannot be used with the error-summary (--error-summary, -es) or list-errors (--list-errors, -le) options. Use \"lozgrep help find\" to see the options of the find command.");
+            process::exit(1);
+        }
 
-            if check_path.is_file() {
-                println!("Invalid syntax. When using simple-find (--simple-find, -sf) the path specified needs to be a directory. simple-find searches a directory for a file. Use \"--help\" or \"-h\" to see options and syntax."); 
+        if option_passed(borrow_passed_options, "--locate") == true { // The database only holds the paths, there is no metadata to check and no archive to open.
+            let unsupported: Vec<&str> = ["--size", "--mtime", "--atime", "--user", "--group", "--perm", "--one-file-system", "--search-archives"].into_iter().filter(|long| option_passed(borrow_passed_options, long)).collect();
+            if !unsupported.is_empty() {
+                print_line!("Invalid syntax. The locate (--locate, -lo) option cannot be used with: {}. The filename database only holds the paths. Use \"lozgrep help find\" to see the options of the find command.", unsupported.join(", "));
                 process::exit(1);
-            } 
+            }
+        }
+
+        check_directory_paths(borrow_valid_paths, "find (--simple-find, -sf)", "find");
+    }
+
+    fn check_directory_paths(borrow_valid_paths: &Vec<String>, borrow_command_name: &str, borrow_command: &str) { // find, index and grep --indexed walk directories.
+        let files: Vec<String> = borrow_valid_paths.iter().filter(|path| Path::new(path).is_file()).cloned().collect();
+
+        if !files.is_empty() {
+            print_line!("Invalid syntax. When using {} the path specified needs to be a directory. {} walks the files in a directory. These paths are files: {}. Use \"lozgrep help {}\" to see the options of the {} command.", borrow_command_name, borrow_command, files.join(", "), borrow_command, borrow_command);
+            process::exit(1);
         }
     }
 
-    #[derive(Debug)] // Instructs the compiler to automatically generate an implementation of the Debug trait for your struct. Has to do this in order for this line in main to work:  if passed_options.verbose == true { println!("Collected {:?}", &passed_options) }.
+    #[derive(Debug)] // Instructs the compiler to automatically generate an implementation of the Debug trait for your struct. Has to do this in order for this line in main to work:  if passed_options.verbose == true { print_line!("Collected {:?}", &passed_options) }.
     pub struct Options {
         pub help: bool,
         pub version: bool,
@@ -317,143 +722,3437 @@ pub mod parse_and_build_arguments {
         pub path: bool,
         pub simple_grep: bool,
         pub simple_find: bool,
+        pub simple_replace: bool,
+        pub simple_index: bool,
+        pub simple_updatedb: bool,
+        pub locate: bool, // find reads the filename database instead of walking.
+        pub max_age: Duration, // How old the filename database can be before locate warns about it.
+        pub indexed: bool, // grep narrows the files with the trigram index of the directories.
+        pub multiline: bool, // grep prints every line a match covers, a match can go over line breaks.
+        pub max_count: usize, // grep stops reading a file after this many matching lines. usize::MAX if it was not passed.
+        pub result_limit: usize, // grep and find stop after this many results in total. 1 with quiet, usize::MAX if neither was passed.
+        pub quiet: bool, // grep and find print nothing, the exit code says if something was found.
+        pub watch: bool, // grep follows the files and watches the directories instead of exiting.
+        pub in_place: bool, // replace rewrites the files instead of printing a diff.
+        pub regex: bool,
+        pub search_zip: bool,
+        pub search_archives: bool, // The members of tar and zip files are matched and searched like files.
+        pub follow: bool,
+        pub one_file_system: bool,
+        pub quiet_errors: bool,
+        pub error_summary: bool,
+        pub list_errors: bool,
+        pub metadata_filters: MetadataFilters,
+        pub file_type_filter: FileTypeFilter,
+        pub pattern_matcher: PatternMatcher, // Holds the query and the lines of the patterns file. simple_grep and simple_find match with this instead of the query_item.
         pub query_item: String,
-        pub path_item: String,
-    }
-
-    impl Options { // ["--help", "-h", "--version", "-ver", "--verbose", "-v", "--query", "-q", "--path", "-p", "--simple-grep", "-sg", "--simple-find", "-sf"] all the options for reference.
-        fn build_options(build_options: Vec<String>, build_query: String, build_path: String) -> Options { // Assign everything.
-            let help: bool = if build_options.contains(&"--help".to_string()) || build_options.contains(&"-h".to_string()) { true } else { false };
-            let version: bool = if build_options.contains(&"--version".to_string()) || build_options.contains(&"-ver".to_string()) { true } else { false };
-            let verbose: bool = if build_options.contains(&"--verbose".to_string()) || build_options.contains(&"-v".to_string()) { true } else { false };
-            let query: bool = if build_options.contains(&"--query".to_string()) || build_options.contains(&"-q".to_string()) { true } else { false };
-            let path: bool = if build_options.contains(&"--path".to_string()) || build_options.contains(&"-p".to_string()) { true } else { false };
-            let simple_grep: bool = if build_options.contains(&"--simple-grep".to_string()) || build_options.contains(&"-sg".to_string()) { true } else { false };
-            let simple_find: bool = if build_options.contains(&"--simple-find".to_string()) || build_options.contains(&"-sf".to_string()) { true } else { false };
+        pub replacement_item: Option<String>, // The text replace puts in place of every match. None if replace is not the command.
+        pub only_matching: bool, // grep prints the matches instead of the lines.
+        pub output_template: Option<String>, // grep prints this for every match, with the capture groups filled in.
+        pub path_items: Vec<String>, // Every path to search, in the order they were passed.
+        pub config_file: Option<String>, // The config file the default options were read from, None if there was no config file or --no-config was passed.
+        pub setting_sources: Vec<(&'static str, SettingSource)>, // Where every option in the option table was resolved from. Shown by --verbose.
+        pub help_topic: Option<OptionScope>, // Some(Grep) or Some(Find) prints the help for one mode, None prints the full help.
+        pub generate_man_page: bool,
+        pub generate_completions: Option<String>, // The shell to print the completion script for.
+    }
+
+    impl Options {
+        #[allow(clippy::too_many_arguments)] // Every parsed part of the configuration is handed over here once, a struct for the arguments would just be Options again.
+        fn build_options(build_options: &Vec<PassedOption>, build_query: String, build_paths: Vec<String>, build_metadata_filters: MetadataFilters, build_file_type_filter: FileTypeFilter, build_pattern_matcher: PatternMatcher, build_config_file: Option<String>, build_help_topic: Option<OptionScope>) -> Options { // Assign everything.
+            let help: bool = option_passed(build_options, "--help");
+            let version: bool = option_passed(build_options, "--version");
+            let verbose: bool = option_passed(build_options, "--verbose");
+            let query: bool = build_query != "null"; // True if the query came from --query or from a positional value.
+            let path: bool = !build_paths.is_empty();
+            let simple_grep: bool = option_passed(build_options, "--simple-grep");
+            let simple_find: bool = option_passed(build_options, "--simple-find");
+            let simple_replace: bool = option_passed(build_options, "--simple-replace");
+            let simple_index: bool = option_passed(build_options, "--simple-index");
+            let simple_updatedb: bool = option_passed(build_options, "--simple-updatedb");
+            let locate: bool = option_passed(build_options, "--locate");
+            let max_age: Duration = option_value(build_options, "--max-age").map(|value| parse_max_age(&value)).unwrap_or(DEFAULT_MAX_AGE);
+            let indexed: bool = option_passed(build_options, "--indexed");
+            let multiline: bool = option_passed(build_options, "--multiline");
+            let max_count: usize = option_value(build_options, "--max-count").map(|value| parse_count(&value, "max-count (--max-count, -mc)")).unwrap_or(usize::MAX);
+            let quiet: bool = option_passed(build_options, "--quiet");
+            let result_limit: usize = if quiet == true { 1 } else { option_value(build_options, "--limit").map(|value| parse_count(&value, "limit (--limit, -li)")).unwrap_or(usize::MAX) }; // One match is enough to know the answer.
+            let watch: bool = option_passed(build_options, "--watch");
+            let in_place: bool = option_passed(build_options, "--in-place");
+            let regex: bool = option_passed(build_options, "--regex");
+            let search_zip: bool = option_passed(build_options, "--search-zip");
+            let search_archives: bool = option_passed(build_options, "--search-archives");
+            let follow: bool = option_passed(build_options, "--follow");
+            let one_file_system: bool = option_passed(build_options, "--one-file-system");
+            let quiet_errors: bool = option_passed(build_options, "--quiet-errors");
+            let error_summary: bool = option_passed(build_options, "--error-summary");
+            let list_errors: bool = option_passed(build_options, "--list-errors");
             let query_item: String = build_query;
-            let path_item:String = build_path;
+            let replacement_item: Option<String> = option_value(build_options, "--replacement");
+            let only_matching: bool = option_passed(build_options, "--only-matching");
+            let output_template: Option<String> = option_value(build_options, "--output");
+            let path_items: Vec<String> = build_paths;
+            let metadata_filters: MetadataFilters = build_metadata_filters;
+            let file_type_filter: FileTypeFilter = build_file_type_filter;
+            let pattern_matcher: PatternMatcher = build_pattern_matcher;
+            let config_file: Option<String> = build_config_file;
+            let help_topic: Option<OptionScope> = build_help_topic;
+            let generate_man_page: bool = option_passed(build_options, "--generate-man-page");
+            let generate_completions: Option<String> = option_value(build_options, "--generate-completions");
+            let setting_sources: Vec<(&'static str, SettingSource)> = OPTION_TABLE.iter().map(|spec| (spec.long, build_options.iter().find(|option| option.long == spec.long).map(|option| option.source).unwrap_or(SettingSource::BuiltIn))).collect();
 
-            return Options {help, version, verbose, query, path, simple_grep, simple_find, query_item, path_item}
+            return Options {help, version, verbose, query, path, simple_grep, simple_find, simple_replace, simple_index, simple_updatedb, locate, max_age, indexed, multiline, max_count, result_limit, quiet, watch, in_place, regex, search_zip, search_archives, follow, one_file_system, quiet_errors, error_summary, list_errors, metadata_filters, file_type_filter, pattern_matcher, query_item, replacement_item, only_matching, output_template, path_items, config_file, setting_sources, help_topic, generate_man_page, generate_completions}
         }
     }
-}
 
-pub mod print_to_terminal { // All print to terminal functions go here.
-    pub fn print_version(borrow_passed_options_verbose: &bool) {
-        println!("lozgrep version 1.0.0");
-        
-        if borrow_passed_options_verbose == &true { println!("VERBOSE: Printed version"); } 
-    }
-
-    pub fn print_help(borrow_passed_options_verbose: &bool) { // TODO: Make more descriptive.
-        println!("Options:");
-        println!("--help          -h       Prints the help menu.");
-        println!("--version       -ver     Prints the current version.");
-        println!("--verbose       -v       Prints output statements while the process is running.");
-        println!("--query         -q       The term you are searching for follows this option.");
-        println!("--path          -p       The path you are searching follows this option.");
-        println!("--simple-grep   -sg      Searches the contents of a file.");
-        println!("--simple-find   -sf      Searches for a file or directory name.");
-        println!("");
-        println!("Syntax rules:");
-        println!("There is no default mode, the options have to be sprecified.");
-        println!("The options can come in any order.");
-        println!("The long option (--) or short option (-) can be used interchangeably.");
-        println!("");
-        println!("Examples:");
-        println!("lozgrep -sg -p /home/user/file -q wordiamlookingfor");
-        println!("lozgrep --help -ver --query filename --simple-find -p /root");
-        println!("");
-        println!("Escape character rules:");
-        println!("The escape character is: /");
-        println!("The escape character can only be used on the value you want to query.");
-        println!("Escape character examples:");
-        println!("If you need to query for phrase that happens to also be an option");
-        println!("lozgrep -sg -q /--help -p /home/user/file");
-        println!("The escape character is needed in this example because without it lozgrep will read --help as an option and not an item to look for.");
-        println!("Therefore if you need to query for / you need to escape it, otherwise it will be stripped and the query will be empty.");
-        println!("lozgrep -sg -q // -p /home/user/file");
-
-        if borrow_passed_options_verbose == &true { println!("VERBOSE: Printed help menu"); }
-    }
-}
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-pub mod execute_main_operations {
-    use std::io;
-    use std::fs;
-    use std::process;
+        type Tokens = (Vec<(&'static str, Option<String>)>, Vec<String>); // (long name, value) pairs and the positional values, so the results are easy to compare.
 
-    pub fn simple_grep(borrow_query_item: &String, borrow_path_item: &String, borrow_passed_options_verbose: &bool) {
-        let contents_result: Result<String, io::Error> = fs::read_to_string(borrow_path_item); // fs::read_to_string takes the file_path, opens that file, and returns a value of type std::io::Result<String> that contains the file’s contents.
-        if borrow_passed_options_verbose == &true { println!("VERBOSE: Attempt to read the contents of {} into a string", borrow_path_item); }
+        fn tokenize(borrow_arguments: &[&str]) -> Result<Tokens, String> {
+            let arguments: Vec<String> = borrow_arguments.iter().map(|argument| argument.to_string()).collect();
+            let (passed_options, positional_values) = split_arguments(&arguments, "")?;
+            return Ok((passed_options.into_iter().map(|option| (option.long, option.value)).collect(), positional_values));
+        }
 
-        let file_contents:String = match contents_result { // Begins a match expression to handle the two possible variants of the contents_result (Ok or Err). Declares contents as a String to store the file contents if reading is successful.
-            Ok(file) => file, // If no error, the contents of the file are passed into file_contents.
-            Err(error_one) => { // If error, print error message and exit.
-            println!("Error. Problem reading the file contents of the given path: {}", error_one);
-            process::exit(1); 
-            }
-        };
-        if borrow_passed_options_verbose == &true { println!("VERBOSE: Successfully read the contents of {} into a string", borrow_path_item); }
+        #[test]
+        fn rest_of_a_short_option_is_always_its_value() {
+            assert_eq!(tokenize(&["-qtype"]).unwrap(), (vec![("--query", Some("type".to_string()))], vec![]));
+            assert_eq!(tokenize(&["-qverbose"]).unwrap(), (vec![("--query", Some("verbose".to_string()))], vec![]));
+            assert_eq!(tokenize(&["-pd"]).unwrap(), (vec![("--path", Some("d".to_string()))], vec![]));
+            assert_eq!(tokenize(&["-quiet"]).unwrap(), (vec![("--query", Some("uiet".to_string()))], vec![]));
+        }
 
-        let mut results_that_match_query: Vec<&str> = Vec::new(); // Mutable vector of &str to hold the found lines.
+        #[test]
+        fn unknown_short_option_without_a_value_prefix_gets_a_suggestion() {
+            let error_message: String = tokenize(&["-vre"]).unwrap_err();
+            assert!(error_message.contains("-vre (did you mean -ver?)"), "{}", error_message);
+        }
+
+        fn options_from(borrow_arguments: &[&str], borrow_source: SettingSource) -> Vec<PassedOption> {
+            let arguments: Vec<String> = borrow_arguments.iter().map(|argument| argument.to_string()).collect();
+            return split_arguments(&arguments, "").unwrap().0.into_iter().map(|option| PassedOption { source: borrow_source, ..option }).collect();
+        }
 
-        if borrow_passed_options_verbose == &true { println!("VERBOSE: Attempt to check if {} contains {}", borrow_path_item, borrow_query_item); }
-        for line in file_contents.lines() { // For every line in file_contents.
-            if line.contains(borrow_query_item) {  // If query_item is in the line.
-                results_that_match_query.push(line); // Push that line into the vector.
-                if borrow_passed_options_verbose == &true { println!("VERBOSE: Found a match"); }
+        #[test]
+        fn command_on_the_command_line_replaces_the_command_in_the_config_file() {
+            for (command, long) in [("-si", "--simple-index"), ("-su", "--simple-updatedb"), ("-sf", "--simple-find")] {
+                let layered_options: Vec<PassedOption> = layer_options(options_from(&["-sg", "-v"], SettingSource::ConfigFile), options_from(&[command], SettingSource::CommandLine));
+                assert_eq!(layered_options.iter().map(|option| option.long).collect::<Vec<&str>>(), vec!["--verbose", long]);
             }
+
+            let layered_options: Vec<PassedOption> = layer_options(options_from(&["-si"], SettingSource::ConfigFile), options_from(&["-su"], SettingSource::CommandLine));
+            assert_eq!(layered_options.iter().map(|option| option.long).collect::<Vec<&str>>(), vec!["--simple-updatedb"]);
         }
 
-        if results_that_match_query.len() == 0 { // If results_that_match_query is empty. Print message and exit.
-            println!("No matches found.");
+        #[test]
+        fn value_stuck_to_a_short_option() {
+            assert_eq!(tokenize(&["-qfoo"]).unwrap(), (vec![("--query", Some("foo".to_string()))], vec![]));
+            assert_eq!(tokenize(&["-tnjson"]).unwrap(), (vec![("--type-not", Some("json".to_string()))], vec![]));
+            assert_eq!(tokenize(&["-sz+10M"]).unwrap(), (vec![("--size", Some("+10M".to_string()))], vec![]));
+        }
 
-            if borrow_passed_options_verbose == &true { println!("VERBOSE: End of process, now exiting"); }
-            process::exit(1);
+        #[test]
+        fn bundled_single_letter_flags() {
+            assert_eq!(tokenize(&["-vh"]).unwrap(), (vec![("--verbose", None), ("--help", None)], vec![]));
+            assert_eq!(tokenize(&["-vq", "foo"]).unwrap(), (vec![("--verbose", None), ("--query", Some("foo".to_string()))], vec![]));
+        }
 
-        } else { // If results_that_match_query is not empty. Print the contents and exit.
-            if borrow_passed_options_verbose == &true { println!("VERBOSE: Will now print the matches"); }
-            for item in results_that_match_query { println!("{}", item); }
+        #[test]
+        fn long_option_with_attached_value() {
+            assert_eq!(tokenize(&["--query="]).unwrap(), (vec![("--query", Some(String::new()))], vec![]));
+            assert_eq!(tokenize(&["--query=a=b"]).unwrap(), (vec![("--query", Some("a=b".to_string()))], vec![]));
 
-            if borrow_passed_options_verbose == &true { println!("VERBOSE: End of process, now exiting"); }
-            process::exit(1);
+            let error_message: String = tokenize(&["--quiet=1"]).unwrap_err();
+            assert!(error_message.contains("does not take a value"), "{}", error_message);
+
+            let error_message: String = tokenize(&["--qiuet=1"]).unwrap_err();
+            assert!(error_message.contains("--qiuet=1 (did you mean --quiet?)"), "{}", error_message);
         }
-    }
 
-    pub fn simple_find(borrow_query_item: &String, borrow_path_item: &String, borrow_passed_options_verbose: &bool) {
-        use walkdir::WalkDir; //  Cross platform Rust library for efficiently walking a directory recursively.
+        #[test]
+        fn everything_after_double_dash_is_positional() {
+            assert_eq!(tokenize(&["-v", "--", "-quiet", "--help", "@file"]).unwrap(), (vec![("--verbose", None)], vec!["-quiet".to_string(), "--help".to_string(), "@file".to_string()]));
+        }
 
-        let mut nothing_found: usize = 0;
+        fn write_argument_file(borrow_name: &str, borrow_contents: &str) -> String { // Returns @path of the new file.
+            let directory: std::path::PathBuf = env::temp_dir().join(format!("lozgrep-argument-files-{}", process::id()));
+            fs::create_dir_all(&directory).unwrap();
+            let file_path: std::path::PathBuf = directory.join(borrow_name);
+            fs::write(&file_path, borrow_contents).unwrap();
+            return format!("@{}", file_path.display());
+        }
 
-        for path_result in WalkDir::new(borrow_path_item) {
-            match path_result { // WalkDir::new(borrow_path_item) return a result.
-                Ok(path) => { // If the process is able to sucessfully access the path.
-                    let path_compare = format!("{}", path.path().display()); // Format the path into a string.
+        #[test]
+        fn argument_file_can_be_included_more_than_once() {
+            let shared: String = write_argument_file("shared", "-v\n");
+            let first: String = write_argument_file("first", &format!("{}\n-qfoo\n", shared));
+            let second: String = write_argument_file("second", &format!("{}\npath\n", shared));
 
-                    if path_compare.contains(borrow_query_item) { // Check if the query_item is contained within the path. 
-                        if borrow_passed_options_verbose == &true { println!("VERBOSE: Found a match"); }
-                        println!("{}", path_compare); // Print the path that matched.
-                        nothing_found = nothing_found + 1;
-                    }
+            assert_eq!(tokenize(&[&shared, &shared]).unwrap(), (vec![("--verbose", None), ("--verbose", None)], vec![]));
+            assert_eq!(tokenize(&[&first, &second]).unwrap(), (vec![("--verbose", None), ("--query", Some("foo".to_string())), ("--verbose", None)], vec!["path".to_string()]));
+            assert_eq!(tokenize(&["--", &shared]).unwrap(), (vec![], vec![shared.clone()]));
+        }
 
-                } Err(error_one) => { // TODO: Create an option that will repress "permission denied" errors.
-                    if let Some(inner) = error_one.io_error() { // This checks if err.io_error() has a detailed I/O error (Some(inner)), which can be further analyzed.
-                        if inner.kind() == std::io::ErrorKind::PermissionDenied { // If it finds an I/O error (Some(inner)), it inspects the error's kind() (like PermissionDenied).
-                            println!("Permission denied: {}", error_one.path().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown path".to_string())); // map() is a method available on Option. It applies the given closure (the function inside map) to the value inside the Some variant and returns a new Option with the transformed value. If path() returns Some(p), the closure |p| p.display().to_string() converts the Path into a string using the display() method. The result is Some(String). If path() returns None, map() does nothing and the result remains None. .unwrap_or_else(|| "unknown path".to_string()). This method is called on the result of map(), which is an Option<String>. If the Option is Some(String), it extracts and returns the string. If the Option is None, it calls the closure provided to unwrap_or_else and uses its return value. Here, the closure returns the string "unknown path". 
-                        } else {
-                            println!("Error: {}", error_one);
-                        }
-                    } else { // If it's None (i.e., no I/O error is associated with err), it skips this block and prints a generic error message.
-                        println!("Error: {}", error_one);
-                    }
-                }
-            }
+        #[test]
+        fn argument_file_that_includes_itself_is_an_error() {
+            let looping_path: String = env::temp_dir().join(format!("lozgrep-argument-files-{}", process::id())).join("looping").display().to_string();
+            write_argument_file("looping", &format!("-v\n@{}\n", looping_path));
+
+            let error_message: String = tokenize(&[&format!("@{}", looping_path)]).unwrap_err();
+            assert!(error_message.contains("includes itself"), "{}", error_message);
         }
 
-        if nothing_found == 0 {println!("No matches found.");} // If matches are found, nothing_found will not equal zero.
+        #[test]
+        fn at_sign_without_a_file_is_a_value() {
+            assert_eq!(tokenize(&["@Override", "src"]).unwrap(), (vec![], vec!["@Override".to_string(), "src".to_string()]));
+            assert_eq!(tokenize(&["-q", "@Override"]).unwrap(), (vec![("--query", Some("@Override".to_string()))], vec![]));
+        }
 
-        if borrow_passed_options_verbose == &true { println!("VERBOSE: End of process, now exiting"); }
-        process::exit(1);
+        #[test]
+        fn missing_value_is_an_error() {
+            let error_message: String = tokenize(&["--query"]).unwrap_err();
+            assert!(error_message.contains("The --query option requires a value"), "{}", error_message);
+        }
+    }
+}
+
+pub mod filter_by_metadata { // Everything for the size, mtime, atime, user, group and perm options goes here.
+    use std::fs;
+    use std::process;
+    use std::time::{Duration, SystemTime};
+
+    #[derive(Debug)]
+    pub struct MetadataFilters { // None means the option was not passed.
+        pub size: Option<Vec<SizeBound>>,
+        pub mtime: Option<TimeBound>,
+        pub atime: Option<TimeBound>,
+        pub user: Option<u32>,
+        pub group: Option<u32>,
+        pub perm: Option<PermissionBits>,
+    }
+
+    #[derive(Debug)]
+    pub enum SizeBound {
+        LargerThan(u64),
+        SmallerThan(u64),
+        Exactly(u64),
+    }
+
+    #[derive(Debug)]
+    pub enum TimeBound {
+        NewerThan(SystemTime),
+        OlderThan(SystemTime),
+    }
+
+    #[derive(Debug)]
+    pub enum PermissionBits {
+        Exactly(u32),
+        AllOf(u32),
+        AnyOf(u32),
+    }
+
+    pub fn build_metadata_filters(borrow_option_values: &Vec<(String, String)>) -> MetadataFilters { // Takes the (long option, value) tuples collected by parse_and_build_arguments. Exits the process if a value is not valid.
+        let mut metadata_filters: MetadataFilters = MetadataFilters { size: None, mtime: None, atime: None, user: None, group: None, perm: None };
+
+        for (option, value) in borrow_option_values {
+            match option.as_str() {
+                "--size" => { metadata_filters.size = Some(parse_size(value)); }
+                "--mtime" => { metadata_filters.mtime = Some(parse_time(value, "mtime (--mtime, -mt)")); }
+                "--atime" => { metadata_filters.atime = Some(parse_time(value, "atime (--atime, -at)")); }
+                "--user" => { metadata_filters.user = Some(parse_owner(value, "/etc/passwd", "user (--user, -us)")); }
+                "--group" => { metadata_filters.group = Some(parse_owner(value, "/etc/group", "group (--group, -gr)")); }
+                "--perm" => { metadata_filters.perm = Some(parse_permission_bits(value)); }
+                _ => {} // The values of the type options are collected into the same vector, they are handled by filter_by_file_type.
+            }
+        }
+
+        return metadata_filters;
+    }
+
+    impl MetadataFilters {
+        pub fn is_empty(&self) -> bool {
+            return self.size.is_none() && self.mtime.is_none() && self.atime.is_none() && self.user.is_none() && self.group.is_none() && self.perm.is_none();
+        }
+
+        pub fn entry_matches(&self, borrow_entry: &walkdir::DirEntry) -> bool { // Returns true if the entry passes every metadata option that was given.
+            if self.is_empty() == true { return true; } // Nothing to check, so the metadata does not need to be read.
+
+            let metadata: fs::Metadata = match borrow_entry.metadata() { // Follows the link if --follow is used, because walkdir was told to.
+                Ok(metadata) => metadata,
+                Err(_) => { return false; } // If the metadata cannot be read, the entry cannot be shown to pass.
+            };
+
+            if let Some(size_bounds) = &self.size {
+                if metadata.is_dir() == true { return false; } // Like find, a size only makes sense for files.
+
+                for bound in size_bounds {
+                    let passes: bool = match bound {
+                        SizeBound::LargerThan(size) => metadata.len() > *size,
+                        SizeBound::SmallerThan(size) => metadata.len() < *size,
+                        SizeBound::Exactly(size) => metadata.len() == *size,
+                    };
+                    if passes == false { return false; }
+                }
+            }
+
+            if let Some(time_bound) = &self.mtime {
+                if time_passes(metadata.modified(), time_bound) == false { return false; }
+            }
+
+            if let Some(time_bound) = &self.atime {
+                if time_passes(metadata.accessed(), time_bound) == false { return false; }
+            }
+
+            if let Some(user_id) = self.user {
+                if owner_ids(&metadata).0 != user_id { return false; }
+            }
+
+            if let Some(group_id) = self.group {
+                if owner_ids(&metadata).1 != group_id { return false; }
+            }
+
+            if let Some(permission_bits) = &self.perm {
+                let mode: u32 = permission_mode(&metadata);
+                let passes: bool = match permission_bits {
+                    PermissionBits::Exactly(bits) => mode == *bits,
+                    PermissionBits::AllOf(bits) => mode & bits == *bits,
+                    PermissionBits::AnyOf(bits) => mode & bits != 0,
+                };
+                if passes == false { return false; }
+            }
+
+            return true;
+        }
+    }
+
+    fn time_passes(borrow_time_result: std::io::Result<SystemTime>, borrow_time_bound: &TimeBound) -> bool {
+        let time: SystemTime = match borrow_time_result {
+            Ok(time) => time,
+            Err(_) => { return false; } // Some file systems do not record access times.
+        };
+
+        match borrow_time_bound {
+            TimeBound::NewerThan(limit) => { return time > *limit; }
+            TimeBound::OlderThan(limit) => { return time < *limit; }
+        }
+    }
+
+    fn parse_size(borrow_value: &String) -> Vec<SizeBound> { // +10M, -1k, 512, or two of them joined by a comma.
+        let mut size_bounds: Vec<SizeBound> = Vec::new();
+
+        for part in borrow_value.split(',') {
+            let (sign, number) = split_sign(part);
+            let bytes: u64 = match parse_size_number(number) {
+                Some(bytes) => bytes,
+                None => {
+                    print_line!("Invalid syntax. The size (--size, -sz) value is not valid: {}. Examples of valid sizes: +10M, -1k, 512, +1k,-10M. Use \"--help\" or \"-h\" to see options and syntax.", borrow_value);
+                    process::exit(1);
+                }
+            };
+
+            match sign {
+                '+' => size_bounds.push(SizeBound::LargerThan(bytes)),
+                '-' => size_bounds.push(SizeBound::SmallerThan(bytes)),
+                _ => size_bounds.push(SizeBound::Exactly(bytes)),
+            }
+        }
+
+        return size_bounds;
+    }
+
+    fn parse_size_number(borrow_number: &str) -> Option<u64> { // 10M becomes 10485760. Units are powers of 1024.
+        let multiplier: u64 = match borrow_number.chars().last() {
+            Some('k') | Some('K') => 1024,
+            Some('m') | Some('M') => 1024 * 1024,
+            Some('g') | Some('G') => 1024 * 1024 * 1024,
+            Some('t') | Some('T') => 1024 * 1024 * 1024 * 1024,
+            _ => 1,
+        };
+        let digits: &str = if multiplier == 1 { borrow_number } else { &borrow_number[..borrow_number.len() - 1] }; // The unit is always one ASCII character, so it is safe to slice it off.
+
+        return digits.parse::<u64>().ok()?.checked_mul(multiplier);
+    }
+
+    fn parse_time(borrow_value: &String, borrow_option_name: &str) -> TimeBound { // -7d, +7d, -/path/to/file, +/path/to/file.
+        let (sign, rest) = split_sign(borrow_value);
+
+        if sign != '+' && sign != '-' {
+            print_line!("Invalid syntax. The {} value has to start with - (newer than) or + (older than): {}. Use \"--help\" or \"-h\" to see options and syntax.", borrow_option_name, borrow_value);
+            process::exit(1);
+        }
+
+        let limit: SystemTime = match parse_duration(rest) {
+            Some(duration) => SystemTime::now().checked_sub(duration).unwrap_or(SystemTime::UNIX_EPOCH), // A duration means that long ago from now.
+            None => match fs::metadata(rest).and_then(|metadata| metadata.modified()) { // If it is not a duration, it has to be a reference file.
+                Ok(limit) => limit,
+                Err(_) => {
+                    print_line!("Invalid syntax. The {} value is not a duration or a readable file: {}. Examples of valid values: -7d, +12h, -/etc/passwd. Use \"--help\" or \"-h\" to see options and syntax.", borrow_option_name, borrow_value);
+                    process::exit(1);
+                }
+            },
+        };
+
+        if sign == '-' { return TimeBound::NewerThan(limit); } else { return TimeBound::OlderThan(limit); }
+    }
+
+    pub fn parse_duration(borrow_duration: &str) -> Option<Duration> { // 30s, 15m, 12h, 7d, 2w. A number without a unit is days, like find.
+        let seconds_per_unit: u64 = match borrow_duration.chars().last()? {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 60 * 60 * 24,
+            'w' => 60 * 60 * 24 * 7,
+            '0'..='9' => { return Some(Duration::from_secs(borrow_duration.parse::<u64>().ok()?.checked_mul(60 * 60 * 24)?)); }
+            _ => { return None; }
+        };
+
+        return Some(Duration::from_secs(borrow_duration[..borrow_duration.len() - 1].parse::<u64>().ok()?.checked_mul(seconds_per_unit)?));
+    }
+
+    fn parse_owner(borrow_value: &String, borrow_database: &str, borrow_option_name: &str) -> u32 { // A numeric value is used as the ID, otherwise the name is looked up in /etc/passwd or /etc/group.
+        if let Ok(id) = borrow_value.parse::<u32>() { return id; }
+
+        let database: String = fs::read_to_string(borrow_database).unwrap_or_default();
+
+        for line in database.lines() { // Each line looks like name:password:id:... in both files.
+            let fields: Vec<&str> = line.split(':').collect();
+
+            if fields.len() > 2 && fields[0] == borrow_value {
+                if let Ok(id) = fields[2].parse::<u32>() { return id; }
+            }
+        }
+
+        print_line!("Invalid syntax. The {} value was not found in {}: {}. Use \"--help\" or \"-h\" to see options and syntax.", borrow_option_name, borrow_database, borrow_value);
+        process::exit(1);
+    }
+
+    fn parse_permission_bits(borrow_value: &String) -> PermissionBits { // 644 is exactly, -644 is all of, /022 is any of.
+        let (prefix, digits): (char, &str) = if borrow_value.starts_with('-') || borrow_value.starts_with('/') { (borrow_value.chars().next().unwrap_or(' '), &borrow_value[1..]) } else { (' ', borrow_value.as_str()) };
+
+        let bits: u32 = match u32::from_str_radix(digits, 8) {
+            Ok(bits) if bits <= 0o7777 => bits,
+            _ => {
+                print_line!("Invalid syntax. The perm (--perm, -pm) value is not a valid octal mode: {}. Examples of valid modes: 644, -644, /022. Use \"--help\" or \"-h\" to see options and syntax.", borrow_value);
+                process::exit(1);
+            }
+        };
+
+        match prefix {
+            '-' => { return PermissionBits::AllOf(bits); }
+            '/' => { return PermissionBits::AnyOf(bits); }
+            _ => { return PermissionBits::Exactly(bits); }
+        }
+    }
+
+    fn split_sign(borrow_value: &str) -> (char, &str) { // Splits +10M into ('+', "10M"). If there is no sign, the char is a space.
+        if borrow_value.starts_with('+') || borrow_value.starts_with('-') {
+            return (borrow_value.chars().next().unwrap_or(' '), &borrow_value[1..]);
+        }
+
+        return (' ', borrow_value);
+    }
+
+    #[cfg(unix)]
+    fn owner_ids(borrow_metadata: &fs::Metadata) -> (u32, u32) { // Returns (user ID, group ID).
+        use std::os::unix::fs::MetadataExt;
+        return (borrow_metadata.uid(), borrow_metadata.gid());
+    }
+
+    #[cfg(not(unix))]
+    fn owner_ids(_borrow_metadata: &fs::Metadata) -> (u32, u32) { // Ownership is not exposed on this platform.
+        return (0, 0);
+    }
+
+    #[cfg(unix)]
+    fn permission_mode(borrow_metadata: &fs::Metadata) -> u32 { // Only the permission bits, the file type bits are masked out.
+        use std::os::unix::fs::MetadataExt;
+        return borrow_metadata.mode() & 0o7777;
+    }
+
+    #[cfg(not(unix))]
+    fn permission_mode(_borrow_metadata: &fs::Metadata) -> u32 { // Permission bits are not exposed on this platform.
+        return 0;
+    }
+}
+
+pub mod filter_by_file_type { // Everything for the type, type-not and type-add options goes here.
+    use std::process;
+
+    const BUILT_IN_TYPES: [(&str, &[&str]); 28] = [ // (type name, file name patterns). * matches any number of characters, ? matches one character.
+        ("c", &["*.c", "*.h"]),
+        ("cargo", &["Cargo.toml", "Cargo.lock"]),
+        ("cpp", &["*.cpp", "*.cc", "*.cxx", "*.hpp", "*.hh", "*.hxx", "*.h"]),
+        ("css", &["*.css", "*.scss", "*.sass", "*.less"]),
+        ("docker", &["Dockerfile", "*.dockerfile", "Containerfile"]),
+        ("go", &["*.go"]),
+        ("html", &["*.html", "*.htm", "*.xhtml"]),
+        ("java", &["*.java"]),
+        ("js", &["*.js", "*.mjs", "*.cjs", "*.jsx"]),
+        ("json", &["*.json", "*.jsonl"]),
+        ("log", &["*.log"]),
+        ("lua", &["*.lua"]),
+        ("make", &["Makefile", "makefile", "GNUmakefile", "*.mk"]),
+        ("md", &["*.md", "*.markdown"]),
+        ("php", &["*.php"]),
+        ("py", &["*.py", "*.pyi"]),
+        ("rb", &["*.rb", "Gemfile", "Rakefile"]),
+        ("rust", &["*.rs"]),
+        ("sh", &["*.sh", "*.bash", "*.zsh", ".bashrc", ".zshrc", ".profile"]),
+        ("sql", &["*.sql"]),
+        ("swift", &["*.swift"]),
+        ("toml", &["*.toml"]),
+        ("ts", &["*.ts", "*.tsx", "*.mts", "*.cts"]),
+        ("txt", &["*.txt"]),
+        ("xml", &["*.xml", "*.xsd", "*.xsl"]),
+        ("yaml", &["*.yaml", "*.yml"]),
+        ("zig", &["*.zig"]),
+        ("conf", &["*.conf", "*.cfg", "*.ini"]),
+    ];
+
+    #[derive(Debug)]
+    pub struct FileTypeFilter { // Holds the file name patterns of the selected types. Both vectors are empty if the options were not passed.
+        pub include_patterns: Vec<String>,
+        pub exclude_patterns: Vec<String>,
+    }
+
+    pub fn built_in_type_names() -> String { // Used by the help menu and the error messages.
+        let mut names: Vec<&str> = BUILT_IN_TYPES.iter().map(|(name, _)| *name).collect();
+        names.sort();
+        return names.join(", ");
+    }
+
+    pub fn build_file_type_filter(borrow_option_values: &Vec<(String, String)>) -> FileTypeFilter { // Takes the (option, value) tuples collected by parse_and_build_arguments. Exits the process if a type is not known.
+        let mut custom_types: Vec<(String, Vec<String>)> = Vec::new();
+        let mut file_type_filter: FileTypeFilter = FileTypeFilter { include_patterns: Vec::new(), exclude_patterns: Vec::new() };
+
+        for (option, value) in borrow_option_values { // The custom types have to be known before type and type-not can be resolved, so they are collected first.
+            if option == "--type-add" || option == "-ta" {
+                custom_types.append(&mut parse_custom_types(value));
+            }
+        }
+
+        for (option, value) in borrow_option_values {
+            if option == "--type" || option == "-t" {
+                file_type_filter.include_patterns = resolve_type_names(value, &custom_types);
+            } else if option == "--type-not" || option == "-tn" {
+                file_type_filter.exclude_patterns = resolve_type_names(value, &custom_types);
+            }
+        }
+
+        return file_type_filter;
+    }
+
+    fn parse_custom_types(borrow_value: &String) -> Vec<(String, Vec<String>)> { // web:*.html,*.css;conf:*.conf becomes [("web", ["*.html", "*.css"]), ("conf", ["*.conf"])].
+        let mut custom_types: Vec<(String, Vec<String>)> = Vec::new();
+
+        for definition in borrow_value.split(';').filter(|definition| !definition.is_empty()) {
+            let (name, patterns) = match definition.split_once(':') {
+                Some((name, patterns)) if !name.is_empty() && !patterns.is_empty() => (name, patterns),
+                _ => {
+                    print_line!("Invalid syntax. The type-add (--type-add, -ta) value has to look like name:pattern,pattern: {}. Use \"--help\" or \"-h\" to see options and syntax.", definition);
+                    process::exit(1);
+                }
+            };
+
+            custom_types.push((name.to_string(), patterns.split(',').filter(|pattern| !pattern.is_empty()).map(|pattern| pattern.to_string()).collect()));
+        }
+
+        return custom_types;
+    }
+
+    fn resolve_type_names(borrow_value: &String, borrow_custom_types: &Vec<(String, Vec<String>)>) -> Vec<String> { // rust,py becomes ["*.rs", "*.py", "*.pyi"]. A custom type with the same name as a built-in type replaces it.
+        let mut patterns: Vec<String> = Vec::new();
+
+        for name in borrow_value.split(',').filter(|name| !name.is_empty()) {
+            if let Some((_, custom_patterns)) = borrow_custom_types.iter().find(|(custom_name, _)| custom_name == name) {
+                patterns.extend(custom_patterns.iter().cloned());
+
+            } else if let Some((_, built_in_patterns)) = BUILT_IN_TYPES.iter().find(|(built_in_name, _)| *built_in_name == name) {
+                patterns.extend(built_in_patterns.iter().map(|pattern| pattern.to_string()));
+
+            } else {
+                print_line!("Invalid syntax. Unknown file type: {}. The built-in types are: {}. Custom types can be made with the type-add (--type-add, -ta) option. Use \"--help\" or \"-h\" to see options and syntax.", name, built_in_type_names());
+                process::exit(1);
+            }
+        }
+
+        return patterns;
+    }
+
+    impl FileTypeFilter {
+        pub fn is_empty(&self) -> bool {
+            return self.include_patterns.is_empty() && self.exclude_patterns.is_empty();
+        }
+
+        pub fn file_name_matches(&self, borrow_file_name: &str) -> bool { // Returns true if the file name is one of the included types and none of the excluded types.
+            if self.include_patterns.len() != 0 && !self.include_patterns.iter().any(|pattern| pattern_matches(pattern.as_bytes(), borrow_file_name.as_bytes())) {
+                return false;
+            }
+
+            if self.exclude_patterns.iter().any(|pattern| pattern_matches(pattern.as_bytes(), borrow_file_name.as_bytes())) {
+                return false;
+            }
+
+            return true;
+        }
+    }
+
+    fn pattern_matches(borrow_pattern: &[u8], borrow_name: &[u8]) -> bool { // Small glob matcher. * matches any number of characters, ? matches one character, everything else has to be equal.
+        let mut pattern_index: usize = 0;
+        let mut name_index: usize = 0;
+        let mut last_star: Option<(usize, usize)> = None; // (pattern index after the *, name index the * started matching from). Used to backtrack when a later character does not match.
+
+        while name_index < borrow_name.len() {
+            if pattern_index < borrow_pattern.len() && (borrow_pattern[pattern_index] == b'?' || borrow_pattern[pattern_index] == borrow_name[name_index]) {
+                pattern_index += 1;
+                name_index += 1;
+
+            } else if pattern_index < borrow_pattern.len() && borrow_pattern[pattern_index] == b'*' {
+                last_star = Some((pattern_index + 1, name_index));
+                pattern_index += 1;
+
+            } else if let Some((star_pattern_index, star_name_index)) = last_star { // Let the last * match one more character and try again.
+                pattern_index = star_pattern_index;
+                name_index = star_name_index + 1;
+                last_star = Some((star_pattern_index, star_name_index + 1));
+
+            } else {
+                return false;
+            }
+        }
+
+        while pattern_index < borrow_pattern.len() && borrow_pattern[pattern_index] == b'*' { pattern_index += 1; } // Trailing stars match nothing.
+
+        return pattern_index == borrow_pattern.len();
+    }
+}
+
+pub mod match_many_patterns { // Everything for the query, the patterns-file and the regex option goes here. All patterns are searched in one pass over the text, so hundreds of patterns cost about the same as one.
+    use std::fmt;
+    use std::fs;
+    use std::process;
+    use memchr::memmem;
+    use regex::bytes::{Regex, RegexBuilder};
+
+    const ALPHABET: usize = 256; // Patterns are matched byte by byte, so UTF-8 needs no special handling.
+
+    pub struct PatternMatcher { // An Aho-Corasick automaton. Every state has a transition for every byte, so matching is one table lookup per byte of the text.
+        pub patterns: Vec<String>,
+        transitions: Vec<u32>, // transitions[state * ALPHABET + byte] is the next state. State 0 is the root.
+        accepting: Vec<bool>, // True if a pattern ends in this state, or in one of the states its failure links lead to.
+        match_length: Vec<usize>, // The length of the longest pattern that ends in this state, used to find where the match starts.
+        depth: Vec<usize>, // The length of the text this state stands for. A match that is still going on started at most this many bytes back.
+        literal_finder: Option<memmem::Finder<'static>>, // Used instead of the automaton when there is only one pattern. memmem skips through the text with SIMD and Two-Way, which is a lot faster than one lookup per byte.
+        regex: Option<Regex>, // Used instead of both when --regex is passed. All patterns are joined into one alternation.
+    }
+
+    impl fmt::Debug for PatternMatcher { // The transition table has 256 entries per state, printing it for --verbose would flood the terminal.
+        fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            return write!(formatter, "PatternMatcher {{ patterns: {}, states: {}, regex: {} }}", self.patterns.len(), self.accepting.len(), self.regex.is_some());
+        }
+    }
+
+    pub fn build_pattern_matcher(borrow_query: &String, borrow_patterns_file: Option<String>, borrow_regex: bool) -> PatternMatcher { // Takes the query ("null" if it was not passed), the path of the patterns file and if the patterns are regular expressions. Exits the process if the file cannot be read, has no patterns, or a regular expression is not valid.
+        let mut patterns: Vec<String> = Vec::new();
+
+        if borrow_query != "null" { patterns.push(borrow_query.clone()); }
+
+        if let Some(patterns_file) = borrow_patterns_file {
+            let contents: String = match fs::read_to_string(&patterns_file) {
+                Ok(contents) => contents,
+                Err(error_one) => {
+                    print_line!("Invalid syntax. Problem reading the patterns file {}: {}. Use \"--help\" or \"-h\" to see options and syntax.", patterns_file, error_one);
+                    process::exit(1);
+                }
+            };
+
+            let file_patterns: Vec<String> = contents.lines().map(|line| line.trim_end_matches('\r').to_string()).filter(|line| !line.is_empty()).collect(); // An empty line would match everything, so empty lines are skipped.
+
+            if file_patterns.is_empty() && borrow_query == "null" {
+                print_line!("Invalid syntax. The patterns file {} has no patterns. Use \"--help\" or \"-h\" to see options and syntax.", patterns_file);
+                process::exit(1);
+            }
+
+            patterns.extend(file_patterns);
+        }
+
+        if borrow_regex == true {
+            match PatternMatcher::build_regex(patterns) {
+                Ok(pattern_matcher) => { return pattern_matcher; }
+                Err(error_one) => {
+                    print_line!("Invalid syntax. The regular expression is not valid: {}. Use \"--help\" or \"-h\" to see options and syntax.", error_one);
+                    process::exit(1);
+                }
+            }
+        }
+
+        return PatternMatcher::build(patterns);
+    }
+
+    impl PatternMatcher {
+        pub fn build(patterns: Vec<String>) -> PatternMatcher {
+            let mut transitions: Vec<u32> = vec![0; ALPHABET];
+            let mut accepting: Vec<bool> = vec![false];
+            let mut match_length: Vec<usize> = vec![0];
+            let mut depth: Vec<usize> = vec![0];
+
+            for pattern in &patterns { // Builds the trie. A transition of 0 means there is no child yet, no trie edge can point back to the root.
+                let mut state: usize = 0;
+
+                for byte in pattern.bytes() {
+                    let index: usize = state * ALPHABET + byte as usize;
+
+                    if transitions[index] == 0 {
+                        transitions[index] = accepting.len() as u32;
+                        transitions.extend([0; ALPHABET]);
+                        accepting.push(false);
+                        match_length.push(0);
+                        depth.push(depth[state] + 1);
+                    }
+                    state = transitions[index] as usize;
+                }
+
+                accepting[state] = true; // An empty pattern makes the root accepting, so it matches everything.
+                match_length[state] = pattern.len();
+            }
+
+            let mut failure: Vec<u32> = vec![0; accepting.len()]; // The longest proper suffix of a state that is also a state.
+            let mut queue: std::collections::VecDeque<usize> = std::collections::VecDeque::from([0]);
+
+            while let Some(state) = queue.pop_front() { // Breadth first, so the failure state of every child is already complete when the child is reached.
+                for byte in 0..ALPHABET {
+                    let index: usize = state * ALPHABET + byte;
+                    let fallback: u32 = if state == 0 { 0 } else { transitions[failure[state] as usize * ALPHABET + byte] };
+
+                    if transitions[index] == 0 { // No trie child, so the transition is the one of the failure state. This turns the trie into a full automaton.
+                        transitions[index] = fallback;
+                    } else {
+                        let child: usize = transitions[index] as usize;
+                        failure[child] = fallback;
+                        if accepting[child] == false && accepting[fallback as usize] == true { match_length[child] = match_length[fallback as usize]; } // A pattern that is a suffix of another one ends here too.
+                        accepting[child] = accepting[child] || accepting[fallback as usize];
+                        queue.push_back(child);
+                    }
+                }
+            }
+
+            let literal_finder: Option<memmem::Finder<'static>> = if patterns.len() == 1 { Some(memmem::Finder::new(patterns[0].as_bytes()).into_owned()) } else { None };
+
+            return PatternMatcher { patterns, transitions, accepting, match_length, depth, literal_finder, regex: None };
+        }
+
+        pub fn build_regex(patterns: Vec<String>) -> Result<PatternMatcher, regex::Error> { // ^ and $ match at every line break, so a pattern means the same on a line as on the whole buffer.
+            let alternation: String = patterns.iter().map(|pattern| format!("(?:{})", pattern)).collect::<Vec<String>>().join("|");
+            let regex: Regex = RegexBuilder::new(&alternation).multi_line(true).crlf(true).build()?;
+
+            return Ok(PatternMatcher { patterns, transitions: Vec::new(), accepting: Vec::new(), match_length: Vec::new(), depth: Vec::new(), literal_finder: None, regex: Some(regex) });
+        }
+
+        pub fn is_match(&self, borrow_haystack: &[u8]) -> bool { // True if any pattern is found in the haystack.
+            return self.find_match(borrow_haystack).is_some();
+        }
+
+        fn find_match(&self, borrow_haystack: &[u8]) -> Option<(usize, usize)> { // Returns the start and end of the first match, or None if no pattern is found.
+            if let Some(regex) = &self.regex {
+                return regex.find(borrow_haystack).map(|found| (found.start(), found.end()));
+            }
+
+            if let Some(finder) = &self.literal_finder {
+                return finder.find(borrow_haystack).map(|start| (start, start + finder.needle().len()));
+            }
+
+            if self.accepting[0] == true { return Some((0, 0)); }
+
+            let mut state: usize = 0;
+            for (index, byte) in borrow_haystack.iter().enumerate() {
+                state = self.transitions[state * ALPHABET + *byte as usize] as usize;
+                if self.accepting[state] == true { return Some((index + 1 - self.match_length[state], index + 1)); }
+            }
+
+            return None;
+        }
+
+        fn find_longest_match(&self, borrow_haystack: &[u8]) -> Option<(usize, usize)> { // Like find_match, but returns the match that starts first, and the longest of those. With abcd and bc, abcd is found in abcd, not bc. Used by replace, a line only needs to know that there is a match.
+            if self.regex.is_some() || self.literal_finder.is_some() { return self.find_match(borrow_haystack); } // The regex is leftmost-first already, one literal has only one length.
+
+            let mut longest_match: Option<(usize, usize)> = if self.accepting[0] == true { Some((0, 0)) } else { None };
+            let mut state: usize = 0;
+
+            for (index, byte) in borrow_haystack.iter().enumerate() {
+                state = self.transitions[state * ALPHABET + *byte as usize] as usize;
+                if let Some((longest_start, _)) = longest_match {
+                    if index + 1 - self.depth[state] > longest_start { break; } // Every match that is still going on starts after the one that was found, it cannot replace it.
+                }
+
+                if self.accepting[state] == true {
+                    let (match_start, match_end): (usize, usize) = (index + 1 - self.match_length[state], index + 1); // The longest pattern that ends here also starts first.
+                    match longest_match {
+                        Some((longest_start, longest_end)) if longest_start < match_start || (longest_start == match_start && longest_end >= match_end) => {}
+                        _ => { longest_match = Some((match_start, match_end)); }
+                    }
+                }
+            }
+
+            return longest_match;
+        }
+
+        pub fn matching_lines<'a>(&'a self, borrow_buffer: &'a str) -> MatchingLines<'a> { // Same result as checking every line of buffer.lines(), but the whole buffer is searched and the line boundaries are only found around the hits. The lines are found one at a time, so the first one can be printed before the rest of the buffer is searched.
+            return MatchingLines { matcher: self, buffer: borrow_buffer, search_from: 0 };
+        }
+
+        pub fn matching_blocks<'a>(&'a self, borrow_buffer: &'a str) -> MatchingBlocks<'a> { // For multiline. Every match with all the lines it covers, as (number of the first line, the lines). Blocks that share a line are joined, so no line is printed twice.
+            return MatchingBlocks { matcher: self, buffer: borrow_buffer, search_from: 0, line_number: 1, line_start: 0, counted_to: 0, last_match_end: None, open_block: None };
+        }
+
+        pub fn replacements(&self, borrow_buffer: &str, borrow_replacement: &str) -> Vec<(usize, usize, String)> { // Every match that does not overlap an earlier one, as (start, end, replacement text). With --regex, $1 and ${name} in the replacement are filled in from the match.
+            let bytes: &[u8] = borrow_buffer.as_bytes();
+            let mut replacements: Vec<(usize, usize, String)> = Vec::new();
+
+            if let Some(regex) = &self.regex {
+                for captures in regex.captures_iter(bytes) {
+                    let found = captures.get(0).expect("group 0 is always the whole match");
+                    if found.start() == bytes.len() && found.start() > 0 && bytes[found.start() - 1] == b'\n' { continue; } // An empty match after the last line break, like ^ at the very end, is not on a line.
+                    let mut replacement: Vec<u8> = Vec::new();
+                    captures.expand(borrow_replacement.as_bytes(), &mut replacement);
+                    replacements.push((found.start(), found.end(), String::from_utf8_lossy(&replacement).into_owned()));
+                }
+
+                return replacements;
+            }
+
+            let mut search_from: usize = 0;
+            while search_from < bytes.len() {
+                match self.find_longest_match(&bytes[search_from..]) {
+                    Some((start, end)) if end > start => {
+                        replacements.push((search_from + start, search_from + end, borrow_replacement.to_string()));
+                        search_from = search_from + end;
+                    }
+                    _ => break, // An empty literal pattern matches nothing that can be replaced.
+                }
+            }
+
+            return replacements;
+        }
+    }
+
+    pub struct MatchingLines<'a> {
+        matcher: &'a PatternMatcher,
+        buffer: &'a str,
+        search_from: usize, // Where the next search starts, always at the start of a line.
+    }
+
+    impl<'a> Iterator for MatchingLines<'a> {
+        type Item = &'a str;
+
+        fn next(&mut self) -> Option<&'a str> {
+            let bytes: &[u8] = self.buffer.as_bytes();
+
+            while self.search_from < bytes.len() {
+                let search_from: usize = self.search_from;
+                let (match_start, match_end): (usize, usize) = match self.matcher.find_match(&bytes[search_from..]) {
+                    Some((start, end)) => (search_from + start, search_from + end),
+                    None => break,
+                };
+
+                let line_start: usize = memchr::memrchr(b'\n', &bytes[search_from..match_start]).map(|index| search_from + index + 1).unwrap_or(search_from);
+                if line_start == bytes.len() { break; } // An empty match after the last line break, lines() does not count that as a line.
+                let line_end: usize = memchr::memchr(b'\n', &bytes[match_start..]).map(|index| match_start + index).unwrap_or(bytes.len());
+                let line: &'a str = self.buffer[line_start..line_end].strip_suffix('\r').unwrap_or(&self.buffer[line_start..line_end]); // Like lines(), a \r before the \n is not part of the line.
+
+                self.search_from = line_end + 1; // One match is enough for a line, the search goes on at the next line.
+                if match_end <= line_start + line.len() || self.matcher.is_match(line.as_bytes()) { return Some(line); } // A match that goes past the end of the line only counts if the line matches on its own.
+            }
+
+            self.search_from = bytes.len();
+            return None;
+        }
+    }
+
+    pub struct MatchingBlocks<'a> {
+        matcher: &'a PatternMatcher,
+        buffer: &'a str,
+        search_from: usize,
+        line_number: usize, // The number of the line that counted_to is on.
+        line_start: usize, // The start of the line that counted_to is on.
+        counted_to: usize, // The line breaks before this byte are already counted in line_number, so every byte is only counted once.
+        last_match_end: Option<usize>, // Like regex::find_iter, an empty match right where the last match ended is skipped.
+        open_block: Option<(usize, usize, usize)>, // (number of the first line, start of the first line, end of the last line). A block is only finished when the next match starts after it, because that match could still reach into its last line.
+    }
+
+    impl<'a> Iterator for MatchingBlocks<'a> {
+        type Item = (usize, &'a str);
+
+        fn next(&mut self) -> Option<(usize, &'a str)> {
+            let bytes: &[u8] = self.buffer.as_bytes();
+
+            while self.search_from <= bytes.len() {
+                let search_from: usize = self.search_from;
+                let found: Option<(usize, usize)> = match &self.matcher.regex {
+                    Some(regex) => regex.find_at(bytes, search_from).map(|found| (found.start(), found.end())), // find_at still sees the text before search_from, so ^ and \b do not match in the middle of a line.
+                    None => self.matcher.find_match(&bytes[search_from..]).map(|(start, end)| (search_from + start, search_from + end)),
+                };
+                let (match_start, match_end): (usize, usize) = match found {
+                    Some(found) => found,
+                    None => break,
+                };
+                self.search_from = if match_end > match_start { match_end } else { match_start + 1 }; // An empty match would be found again at the same place.
+                if match_end == match_start && self.last_match_end == Some(match_start) { continue; }
+                self.last_match_end = Some(match_end);
+                if match_start == bytes.len() && match_start > 0 && bytes[match_start - 1] == b'\n' { break; } // An empty match after the last line break is not on a line.
+
+                let skipped_bytes: &[u8] = &bytes[self.counted_to..match_start];
+                if let Some(last_break) = memchr::memrchr(b'\n', skipped_bytes) {
+                    self.line_start = self.counted_to + last_break + 1;
+                    self.line_number = self.line_number + memchr::memchr_iter(b'\n', skipped_bytes).count();
+                }
+                self.counted_to = match_start;
+
+                let line_start: usize = self.line_start;
+                let last_byte: usize = if match_end > match_start { match_end - 1 } else { match_start }; // A match that ends with a line break does not cover the line after it.
+                let line_end_after = |byte: usize| memchr::memchr(b'\n', &bytes[byte..]).map(|index| byte + index).unwrap_or(bytes.len());
+
+                match &mut self.open_block {
+                    Some(open_block) if line_start <= open_block.2 => { if last_byte > open_block.2 { open_block.2 = line_end_after(last_byte); } } // A match inside of the block does not need its line end, so many matches on one long line do not scan it again and again.
+                    _ => {
+                        let finished_block: Option<(usize, usize, usize)> = self.open_block.replace((self.line_number, line_start, line_end_after(last_byte)));
+                        if let Some((first_line_number, start, end)) = finished_block { return Some((first_line_number, &self.buffer[start..end])); }
+                    }
+                }
+            }
+
+            self.search_from = bytes.len() + 1;
+            return self.open_block.take().map(|(first_line_number, start, end)| (first_line_number, &self.buffer[start..end]));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn naive_matching_lines<'a>(borrow_patterns: &[&str], borrow_buffer: &'a str) -> Vec<&'a str> { // What matching_lines has to return: every line that contains any of the patterns.
+            return borrow_buffer.lines().filter(|line| borrow_patterns.iter().any(|pattern| line.contains(pattern))).collect();
+        }
+
+        fn assert_same_lines(borrow_patterns: &[&str], borrow_buffer: &str) {
+            let pattern_matcher: PatternMatcher = PatternMatcher::build(borrow_patterns.iter().map(|pattern| pattern.to_string()).collect());
+            let matching_lines: Vec<&str> = pattern_matcher.matching_lines(borrow_buffer).collect();
+            assert_eq!(matching_lines, naive_matching_lines(borrow_patterns, borrow_buffer), "patterns {:?}, buffer {:?}", borrow_patterns, borrow_buffer);
+        }
+
+        #[test]
+        fn failure_links() {
+            assert_same_lines(&["abcd", "bcx"], "abcx\nabcd\nabc\nbcx\n");
+            assert_same_lines(&["aab", "ab"], "aaab\naaa\nxab\n");
+            assert_same_lines(&["abab", "bac"], "ababac\nabac\nbabab\n");
+        }
+
+        #[test]
+        fn patterns_that_are_suffixes_of_each_other() {
+            assert_same_lines(&["he", "she", "his", "hers"], "ushers\nshe\nhi\nthis\nh\n");
+            assert_same_lines(&["abc", "bc", "c"], "ab\nxc\nbcd\nabc\n");
+            assert_same_lines(&["c", "bc", "abc"], "ab\nxc\nbcd\nabc\n");
+        }
+
+        #[test]
+        fn empty_pattern_matches_every_line() {
+            assert_same_lines(&["", "x"], "a\n\nb\n");
+            assert_same_lines(&[""], "a\n\nb");
+        }
+
+        #[test]
+        fn non_ascii_bytes() {
+            assert_same_lines(&["größe", "ß"], "Größe\ngröße\nstrasse\nfuß\n");
+            assert_same_lines(&["日本", "本語"], "日本語\n本\n語本\n");
+            assert_same_lines(&["ÿ", "é"], "ÿ\ny\ncafé\ncafe\n");
+        }
+
+        #[test]
+        fn line_endings() {
+            assert_same_lines(&["a", "b"], "a\r\nb\r\nc\r\n");
+            assert_same_lines(&["a", "b"], "c\na");
+            assert_same_lines(&["a", "b"], "");
+        }
+
+        #[test]
+        fn single_pattern_uses_the_same_lines() {
+            assert_same_lines(&["ab"], "ab\nb\naab\n");
+            assert_same_lines(&["größe"], "Größe\ngröße\n");
+        }
+
+        fn replaced_spans(borrow_patterns: &[&str], borrow_buffer: &str) -> Vec<(usize, usize)> {
+            let pattern_matcher: PatternMatcher = PatternMatcher::build(borrow_patterns.iter().map(|pattern| pattern.to_string()).collect());
+            return pattern_matcher.replacements(borrow_buffer, "X").into_iter().map(|(start, end, _)| (start, end)).collect();
+        }
+
+        fn naive_replaced_spans(borrow_patterns: &[&str], borrow_buffer: &str) -> Vec<(usize, usize)> { // At every position the longest pattern that starts there, then the search goes on after it.
+            let mut spans: Vec<(usize, usize)> = Vec::new();
+            let mut position: usize = 0;
+
+            while position < borrow_buffer.len() {
+                match borrow_patterns.iter().filter(|pattern| !pattern.is_empty() && borrow_buffer.as_bytes()[position..].starts_with(pattern.as_bytes())).map(|pattern| pattern.len()).max() {
+                    Some(length) => { spans.push((position, position + length)); position = position + length; }
+                    None => { position = position + 1; }
+                }
+            }
+
+            return spans;
+        }
+
+        #[test]
+        fn replace_takes_the_leftmost_longest_match() {
+            assert_eq!(replaced_spans(&["abcd", "bc"], "abcd"), vec![(0, 4)]);
+            assert_eq!(replaced_spans(&["bc", "abcd"], "abcd xbcx abc"), vec![(0, 4), (6, 8), (11, 13)]);
+            assert_eq!(replaced_spans(&["ab", "abc", "bcd"], "abcd"), vec![(0, 3)]);
+            assert_eq!(replaced_spans(&["a", "ab", "abc"], "abcabx"), vec![(0, 3), (3, 5)]);
+            assert_eq!(replaced_spans(&["aa"], "aaa"), vec![(0, 2)]);
+        }
+
+        #[test]
+        fn replace_fills_in_capture_groups() {
+            let pattern_matcher: PatternMatcher = PatternMatcher::build_regex(vec!["(?P<user>\\w+)@(\\w+)".to_string()]).unwrap();
+            assert_eq!(pattern_matcher.replacements("mail ann@home, bob@work", "$2:${user}"), vec![(5, 13, "home:ann".to_string()), (15, 23, "work:bob".to_string())]);
+        }
+
+        #[test]
+        fn random_patterns_and_buffers() { // Small alphabet, so the patterns overlap a lot and every kind of failure link is built.
+            let mut seed: u64 = 0x2545F4914F6CDD1D;
+            let mut next_number = move |limit: u64| -> u64 { // xorshift, the same cases on every run.
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                return seed % limit;
+            };
+            let letters: [&str; 4] = ["a", "b", "é", "\n"];
+
+            for _ in 0..2000 {
+                let patterns: Vec<String> = (0..1 + next_number(4)).map(|_| (0..1 + next_number(4)).map(|_| letters[next_number(3) as usize]).collect()).collect();
+                let buffer: String = (0..next_number(40)).map(|_| letters[next_number(4) as usize]).collect();
+                let borrow_patterns: Vec<&str> = patterns.iter().map(|pattern| pattern.as_str()).collect();
+                assert_same_lines(&borrow_patterns, &buffer);
+                assert_eq!(replaced_spans(&borrow_patterns, &buffer), naive_replaced_spans(&borrow_patterns, &buffer), "patterns {:?}, buffer {:?}", borrow_patterns, buffer);
+            }
+        }
+
+        fn regex_blocks(borrow_pattern: &str, borrow_buffer: &str) -> Vec<(usize, String)> {
+            let pattern_matcher: PatternMatcher = PatternMatcher::build_regex(vec![borrow_pattern.to_string()]).unwrap();
+            return pattern_matcher.matching_blocks(borrow_buffer).map(|(line_number, block)| (line_number, block.to_string())).collect();
+        }
+
+        fn naive_regex_lines(borrow_pattern: &str, borrow_buffer: &str) -> Vec<(usize, String)> { // Every line that holds the start of a find_iter match, for patterns that stay on one line. The empty piece after a last line break is not a line.
+            let regex: Regex = RegexBuilder::new(borrow_pattern).multi_line(true).crlf(true).build().unwrap();
+            let mut line_start: usize = 0;
+            return borrow_buffer.split('\n').enumerate().filter_map(|(index, line)| {
+                let start: usize = line_start;
+                line_start = line_start + line.len() + 1;
+                let on_this_line: bool = regex.find_iter(borrow_buffer.as_bytes()).any(|found| found.start() >= start && found.start() <= start + line.len());
+                if on_this_line == true && (line.is_empty() == false || start < borrow_buffer.len() || index == 0) { Some((index + 1, line.to_string())) } else { None }
+            }).collect();
+        }
+
+        #[test]
+        fn blocks_join_the_lines_a_match_covers() {
+            assert_eq!(regex_blocks("b\nc", "a\nb\nc\nd\nb\nc"), vec![(2, "b\nc".to_string()), (5, "b\nc".to_string())]);
+            assert_eq!(regex_blocks("b\nc|d", "a\nb\nc d\ne"), vec![(2, "b\nc d".to_string())]); // d starts on the last line of the block, so it joins it.
+        }
+
+        #[test]
+        fn blocks_of_patterns_that_match_the_empty_string() {
+            for pattern in ["x*", "^", "$", "\\b", "a*", "(?:a\\n)*"] {
+                for buffer in ["", "a", "a\n", "ab\n\nb\naa", "\n\n", "xax\nb"] {
+                    assert_eq!(regex_blocks(pattern, buffer), naive_regex_lines(pattern, buffer), "pattern {:?}, buffer {:?}", pattern, buffer);
+                }
+            }
+        }
+
+        #[test]
+        fn empty_matches_on_a_long_line_are_linear() { // Every byte is an empty match. Scanning the line again for each of them would take minutes.
+            let buffer: String = format!("{}\n{}", "a".repeat(300_000), "b".repeat(300_000));
+            assert_eq!(regex_blocks("x*", &buffer), vec![(1, "a".repeat(300_000)), (2, "b".repeat(300_000))]);
+        }
+    }
+}
+
+pub mod decompress_files { // Everything for the search-zip option goes here. The decoders are only compiled in with the search-zip cargo feature, which is on by default.
+    use std::fs;
+    use std::io;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Compression {
+        Gzip,
+        Bzip2,
+        Xz,
+        Zstd,
+    }
+
+    const MAGIC_BYTES: [(&[u8], Compression); 4] = [ // The first bytes of every format. They are checked before the extension, so a renamed file is still decompressed.
+        (&[0x1f, 0x8b], Compression::Gzip),
+        (b"BZh", Compression::Bzip2),
+        (&[0xfd, b'7', b'z', b'X', b'Z', 0x00], Compression::Xz),
+        (&[0x28, 0xb5, 0x2f, 0xfd], Compression::Zstd),
+    ];
+
+    const EXTENSIONS: [(&str, Compression); 7] = [
+        (".gz", Compression::Gzip),
+        (".tgz", Compression::Gzip),
+        (".bz2", Compression::Bzip2),
+        (".tbz2", Compression::Bzip2),
+        (".xz", Compression::Xz),
+        (".txz", Compression::Xz),
+        (".zst", Compression::Zstd),
+    ];
+
+    pub fn compression_of(borrow_path: &str, borrow_contents: &[u8]) -> Option<Compression> { // None if the file is not compressed, or the format is not one of gzip, bzip2, xz or zstd.
+        if let Some((_, compression)) = MAGIC_BYTES.iter().find(|(magic, _)| borrow_contents.starts_with(magic)) { return Some(*compression); }
+        return EXTENSIONS.iter().find(|(extension, _)| borrow_path.ends_with(extension)).map(|(_, compression)| *compression); // A file that only has the extension fails to decompress, and the error says why.
+    }
+
+    pub fn read_file_contents(borrow_path: &str, borrow_search_zip: bool) -> io::Result<String> { // Same as fs::read_to_string. With search-zip a compressed file is decompressed first, so the matches are found in the text inside.
+        if borrow_search_zip == false { return fs::read_to_string(borrow_path); }
+
+        let contents: Vec<u8> = decompress_if_compressed(borrow_path, fs::read(borrow_path)?)?;
+
+        return String::from_utf8(contents).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")); // The same error fs::read_to_string gives.
+    }
+
+    pub fn decompress_if_compressed(borrow_name: &str, borrow_contents: Vec<u8>) -> io::Result<Vec<u8>> { // The contents are returned as they are if they are not compressed. Also used for the members of an archive, logs.tar can hold .gz files.
+        match compression_of(borrow_name, &borrow_contents) {
+            Some(compression) => { return decompress(&borrow_contents, compression); }
+            None => { return Ok(borrow_contents); }
+        }
+    }
+
+    #[cfg(feature = "search-zip")]
+    pub fn decompress(borrow_contents: &[u8], borrow_compression: Compression) -> io::Result<Vec<u8>> { // Logrotate and pigz write files with more than one member or frame, every one of them is decompressed.
+        use std::io::Read;
+
+        let mut decompressed: Vec<u8> = Vec::new();
+
+        match borrow_compression {
+            Compression::Gzip => { flate2::read::MultiGzDecoder::new(borrow_contents).read_to_end(&mut decompressed)?; }
+            Compression::Bzip2 => { bzip2::read::MultiBzDecoder::new(borrow_contents).read_to_end(&mut decompressed)?; }
+            Compression::Xz => { lzma_rs::xz_decompress(&mut io::BufReader::new(borrow_contents), &mut decompressed).map_err(|error_one| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", error_one)))?; }
+            Compression::Zstd => { decompress_zstd_frames(borrow_contents, &mut decompressed)?; }
+        }
+
+        return Ok(decompressed);
+    }
+
+    #[cfg(feature = "search-zip")]
+    fn decompress_zstd_frames(borrow_contents: &[u8], borrow_decompressed: &mut Vec<u8>) -> io::Result<()> { // ruzstd decodes one frame at a time, so a new decoder is started for every frame. Skippable frames hold metadata and are stepped over.
+        use std::io::Read;
+        use ruzstd::decoding::StreamingDecoder;
+        use ruzstd::decoding::errors::{FrameDecoderError, ReadFrameHeaderError};
+
+        let mut remaining: &[u8] = borrow_contents;
+
+        while remaining.is_empty() == false {
+            match StreamingDecoder::new(&mut remaining) {
+                Ok(mut decoder) => { decoder.read_to_end(borrow_decompressed)?; }
+                Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame { length, .. })) => {
+                    remaining = remaining.get(length as usize..).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the skippable zstd frame is cut off"))?;
+                }
+                Err(error_one) => { return Err(io::Error::new(io::ErrorKind::InvalidData, error_one.to_string())); }
+            }
+        }
+
+        return Ok(());
+    }
+
+    #[cfg(not(feature = "search-zip"))]
+    pub fn decompress(_borrow_contents: &[u8], _borrow_compression: Compression) -> io::Result<Vec<u8>> { // parse_and_build_arguments does not accept --search-zip in this build, this is only reached if it is called from somewhere else.
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "lozgrep was built without the search-zip feature"));
+    }
+}
+
+pub mod read_archive_members { // Everything for the search-archives option goes here. The members of tar and zip files are given virtual paths, release.tar.gz!/etc/app.conf, so find can match their names and grep can search their contents.
+    use std::io;
+
+    pub const MEMBER_SEPARATOR: &str = "!/"; // Between the path of the archive and the path of the member, like jar: URLs and 7-Zip.
+
+    const TAR_EXTENSIONS: [&str; 9] = [".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tbz2", ".tar.xz", ".txz", ".tar.zst", ".tzst"]; // A compressed tar is decompressed with the search-zip decoders first.
+    const ZIP_EXTENSIONS: [&str; 4] = [".zip", ".jar", ".war", ".apk"];
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum ArchiveKind {
+        Tar,
+        Zip,
+    }
+
+    pub type ReadMembers = (Vec<ArchiveMember>, Option<io::Error>); // The members, and the error that ended a tar early. The members before a broken header are still returned.
+
+    pub struct ArchiveMember {
+        pub name: String, // The path inside of the archive, without a leading ./
+        pub is_directory: bool,
+        pub contents: Option<io::Result<Vec<u8>>>, // None if the contents were not asked for, or the member is a directory. An error if only this member could not be read, the other members can still be searched.
+    }
+
+    pub fn archive_kind(borrow_path: &str) -> Option<ArchiveKind> { // The kind is taken from the extension, an archive without one is searched like any other file.
+        let lowercase_path: String = borrow_path.to_lowercase();
+
+        if TAR_EXTENSIONS.iter().any(|extension| lowercase_path.ends_with(extension)) { return Some(ArchiveKind::Tar); }
+        if ZIP_EXTENSIONS.iter().any(|extension| lowercase_path.ends_with(extension)) { return Some(ArchiveKind::Zip); }
+        return None;
+    }
+
+    pub fn virtual_path(borrow_archive_path: &str, borrow_member_name: &str) -> String {
+        return format!("{}{}{}", borrow_archive_path, MEMBER_SEPARATOR, borrow_member_name);
+    }
+
+    #[cfg(feature = "search-archives")]
+    pub fn read_members(borrow_path: &str, borrow_kind: ArchiveKind, borrow_read_contents: bool) -> io::Result<ReadMembers> { // Returns every file and directory in the archive, in the order they are stored. Links and devices inside a tar are skipped.
+        match borrow_kind {
+            ArchiveKind::Tar => { return read_tar_members(borrow_path, borrow_read_contents); }
+            ArchiveKind::Zip => { return read_zip_members(borrow_path, borrow_read_contents).map(|members| (members, None)); }
+        }
+    }
+
+    #[cfg(feature = "search-archives")]
+    fn read_tar_members(borrow_path: &str, borrow_read_contents: bool) -> io::Result<ReadMembers> {
+        use std::io::Read;
+        use crate::decompress_files::decompress_if_compressed;
+
+        let contents: Vec<u8> = decompress_if_compressed(borrow_path, std::fs::read(borrow_path)?)?;
+
+        let mut archive = tar::Archive::new(&contents[..]);
+        let mut members: Vec<ArchiveMember> = Vec::new();
+
+        for entry_result in archive.entries()? { // A broken header ends the archive, the members before it are still returned.
+            let mut entry = match entry_result {
+                Ok(entry) => entry,
+                Err(error_one) => { return Ok((members, Some(error_one))); }
+            };
+            let entry_type: tar::EntryType = entry.header().entry_type();
+            if entry_type.is_file() == false && entry_type.is_dir() == false { continue; }
+
+            let name: String = match entry.path() {
+                Ok(path) => path.to_string_lossy().trim_start_matches("./").trim_end_matches('/').to_string(),
+                Err(error_one) => { return Ok((members, Some(error_one))); }
+            };
+            if name.is_empty() { continue; } // The ./ entry most tar files start with.
+
+            let contents: Option<io::Result<Vec<u8>>> = if borrow_read_contents == true && entry_type.is_file() {
+                let mut member_contents: Vec<u8> = Vec::new();
+                Some(entry.read_to_end(&mut member_contents).map(|_| member_contents))
+            } else {
+                None
+            };
+
+            members.push(ArchiveMember { name, is_directory: entry_type.is_dir(), contents });
+        }
+
+        return Ok((members, None));
+    }
+
+    #[cfg(feature = "search-archives")]
+    fn read_zip_members(borrow_path: &str, borrow_read_contents: bool) -> io::Result<Vec<ArchiveMember>> {
+        use std::io::Read;
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(borrow_path)?).map_err(io::Error::other)?;
+        let mut members: Vec<ArchiveMember> = Vec::new();
+
+        for index in 0..archive.len() {
+            let name: String = archive.name_for_index(index).unwrap_or("unknown member").trim_start_matches("./").trim_end_matches('/').to_string();
+            let mut member = match archive.by_index(index) { // An encrypted member or an unsupported compression only fails this member.
+                Ok(member) => member,
+                Err(error_one) => {
+                    members.push(ArchiveMember { name, is_directory: false, contents: if borrow_read_contents == true { Some(Err(io::Error::other(error_one))) } else { None } });
+                    continue;
+                }
+            };
+
+            let is_directory: bool = member.is_dir();
+
+            let contents: Option<io::Result<Vec<u8>>> = if borrow_read_contents == true && is_directory == false {
+                let mut member_contents: Vec<u8> = Vec::new();
+                Some(member.read_to_end(&mut member_contents).map(|_| member_contents))
+            } else {
+                None
+            };
+
+            members.push(ArchiveMember { name, is_directory, contents });
+        }
+
+        return Ok(members);
+    }
+
+    #[cfg(not(feature = "search-archives"))]
+    pub fn read_members(_borrow_path: &str, _borrow_kind: ArchiveKind, _borrow_read_contents: bool) -> io::Result<ReadMembers> { // parse_and_build_arguments does not accept --search-archives in this build, this is only reached if it is called from somewhere else.
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "lozgrep was built without the search-archives feature"));
+    }
+
+    #[cfg(all(test, feature = "search-archives"))]
+    mod tests {
+        use super::*;
+
+        fn write_tar(borrow_name: &str, borrow_members: &[(&str, &str)], borrow_trailing_bytes: &[u8]) -> String { // Returns the path of a tar with the given members, followed by the given bytes instead of the end of the archive.
+            let mut builder = tar::Builder::new(Vec::new());
+            for (name, contents) in borrow_members {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append_data(&mut header, name, contents.as_bytes()).unwrap();
+            }
+            let mut bytes: Vec<u8> = builder.into_inner().unwrap();
+            bytes.truncate(bytes.len() - 1024); // into_inner writes two empty blocks as the end of the archive.
+            bytes.extend_from_slice(borrow_trailing_bytes);
+
+            let directory: std::path::PathBuf = std::env::temp_dir().join(format!("lozgrep-archives-{}", std::process::id()));
+            std::fs::create_dir_all(&directory).unwrap();
+            let path: std::path::PathBuf = directory.join(borrow_name);
+            std::fs::write(&path, bytes).unwrap();
+            return path.to_string_lossy().to_string();
+        }
+
+        fn names_and_contents(borrow_members: Vec<ArchiveMember>) -> Vec<(String, String)> {
+            return borrow_members.into_iter().map(|member| (member.name, String::from_utf8(member.contents.unwrap().unwrap()).unwrap())).collect();
+        }
+
+        #[test]
+        fn complete_tar() {
+            let path: String = write_tar("complete.tar", &[("a.txt", "first"), ("dir/b.txt", "second")], &[0; 1024]);
+            let (members, error) = read_members(&path, ArchiveKind::Tar, true).unwrap();
+            assert_eq!(names_and_contents(members), vec![("a.txt".to_string(), "first".to_string()), ("dir/b.txt".to_string(), "second".to_string())]);
+            assert!(error.is_none());
+        }
+
+        #[test]
+        fn corrupt_header_keeps_the_members_before_it() {
+            let path: String = write_tar("corrupt.tar", &[("a.txt", "first")], &[b'x'; 512]); // The checksum of this header does not match.
+            let (members, error) = read_members(&path, ArchiveKind::Tar, true).unwrap();
+            assert_eq!(names_and_contents(members), vec![("a.txt".to_string(), "first".to_string())]);
+            assert!(error.is_some(), "the broken header is reported, not taken as the end of the archive");
+        }
+
+        #[test]
+        fn truncated_member_keeps_the_members_before_it() {
+            let mut header = tar::Header::new_gnu();
+            header.set_path("b.txt").unwrap();
+            header.set_size(4096); // The header says there are more bytes than the archive has.
+            header.set_mode(0o644);
+            header.set_cksum();
+            let path: String = write_tar("truncated.tar", &[("a.txt", "first")], header.as_bytes());
+
+            let (members, error) = read_members(&path, ArchiveKind::Tar, true).unwrap();
+            assert_eq!(names_and_contents(members.into_iter().take(1).collect()), vec![("a.txt".to_string(), "first".to_string())]);
+            assert!(error.is_some());
+        }
+    }
+}
+
+pub mod index_file_contents { // Everything for the index command and the indexed option goes here. The index maps every trigram (three bytes in a row) to the files that contain it, so a search only has to read the files that contain every trigram of the query.
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+    use std::env;
+    use std::fs;
+    use std::io;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::time::UNIX_EPOCH;
+    use walkdir::WalkDir;
+
+    const MAGIC: &[u8; 8] = b"LOZIDX01"; // The last two digits are the format version. A different version is rebuilt instead of read.
+
+    pub struct IndexedFile {
+        pub path: String, // Relative to the indexed directory.
+        pub size: u64,
+        pub modified: u64, // Nanoseconds since the Unix epoch. Together with the size it decides if the file has to be read again.
+        pub text: bool, // False for files that are not UTF-8, grep cannot search them so they have no trigrams.
+    }
+
+    pub struct TrigramIndex {
+        pub root: PathBuf, // The canonical path of the indexed directory.
+        pub files: Vec<IndexedFile>, // Sorted by path. The position in this vector is the file ID used by the posting lists.
+        postings: BTreeMap<u32, Vec<u32>>, // Trigram to the sorted IDs of the files that contain it.
+    }
+
+    pub struct UpdateSummary {
+        pub read: usize, // Files that were new or changed, and had to be read.
+        pub removed: usize, // Files that were in the index but are gone now.
+        pub skipped: usize, // Files that could not be read, they are left out of the index.
+    }
+
+    pub fn cache_location(borrow_kind: &str, borrow_root: &Path) -> io::Result<PathBuf> { // $XDG_CACHE_HOME/lozgrep/KIND-HASH, or ~/.cache/lozgrep/KIND-HASH. The hash is taken from the canonical path, so every directory has its own index and database, and the directory itself stays clean.
+        let cache_directory: PathBuf = match env::var("XDG_CACHE_HOME") {
+            Ok(cache_home) if cache_home.is_empty() == false => PathBuf::from(cache_home),
+            _ => match env::var("HOME") {
+                Ok(home) if home.is_empty() == false => Path::new(&home).join(".cache"),
+                _ => { return Err(io::Error::new(io::ErrorKind::NotFound, "neither XDG_CACHE_HOME nor HOME is set, so there is no place for the index")); }
+            },
+        };
+
+        return Ok(cache_directory.join("lozgrep").join(format!("{}-{:016x}", borrow_kind, fnv1a_hash(borrow_root.to_string_lossy().as_bytes()))));
+    }
+
+    fn fnv1a_hash(borrow_bytes: &[u8]) -> u64 { // Small and stable between builds, unlike the hasher of HashMap.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in borrow_bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        return hash;
+    }
+
+    pub fn load_index(borrow_root: &Path) -> io::Result<Option<TrigramIndex>> { // None if the directory was never indexed.
+        let location: PathBuf = cache_location("trigrams", borrow_root)?;
+
+        let contents: Vec<u8> = match fs::read(&location) {
+            Ok(contents) => contents,
+            Err(error_one) if error_one.kind() == io::ErrorKind::NotFound => { return Ok(None); }
+            Err(error_one) => { return Err(error_one); }
+        };
+
+        return decode_index(&contents).map(Some).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("the index {} is damaged or was written by a different version, run lozgrep index again", location.display())));
+    }
+
+    impl TrigramIndex {
+        pub fn empty(root: PathBuf) -> TrigramIndex {
+            return TrigramIndex { root, files: Vec::new(), postings: BTreeMap::new() };
+        }
+
+        pub fn update(&mut self, borrow_follow: bool) -> UpdateSummary { // Walks the directory again. Only the files whose size or modification time changed are read, the trigrams of every other file are kept.
+            let mut summary: UpdateSummary = UpdateSummary { read: 0, removed: 0, skipped: 0 };
+            let mut walked: Vec<(String, PathBuf, u64, u64)> = Vec::new(); // (relative path, full path, size, modified)
+
+            let walker = WalkDir::new(&self.root).follow_links(borrow_follow).into_iter().filter_entry(|entry| entry.file_name() != ".git"); // The history of a git checkout is not source, and it is huge.
+            for entry_result in walker {
+                let entry: walkdir::DirEntry = match entry_result {
+                    Ok(entry) => entry,
+                    Err(_) => { summary.skipped = summary.skipped + 1; continue; }
+                };
+                if entry.file_type().is_file() == false { continue; }
+
+                let metadata: fs::Metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => { summary.skipped = summary.skipped + 1; continue; }
+                };
+                let modified: u64 = metadata.modified().ok().and_then(|time| time.duration_since(UNIX_EPOCH).ok()).map(|duration| duration.as_nanos() as u64).unwrap_or(0);
+                let relative_path: String = entry.path().strip_prefix(&self.root).unwrap_or(entry.path()).to_string_lossy().into_owned();
+
+                walked.push((relative_path, entry.path().to_path_buf(), metadata.len(), modified));
+            }
+            walked.sort_by(|first, second| first.0.cmp(&second.0));
+
+            let old_ids: HashMap<&str, usize> = self.files.iter().enumerate().map(|(id, file)| (file.path.as_str(), id)).collect();
+            let mut old_to_new: Vec<Option<u32>> = vec![None; self.files.len()];
+            let mut files: Vec<IndexedFile> = Vec::with_capacity(walked.len());
+            let mut fresh_trigrams: Vec<(u32, Vec<u32>)> = Vec::new(); // (new ID, trigrams) of the files that were read.
+
+            for (relative_path, full_path, size, modified) in walked {
+                let new_id: u32 = files.len() as u32;
+
+                if let Some(old_id) = old_ids.get(relative_path.as_str()) {
+                    let old_file: &IndexedFile = &self.files[*old_id];
+                    if old_file.size == size && old_file.modified == modified { // Unchanged, its posting list entries are carried over.
+                        old_to_new[*old_id] = Some(new_id);
+                        files.push(IndexedFile { path: relative_path, size, modified, text: old_file.text });
+                        continue;
+                    }
+                }
+
+                let contents: Vec<u8> = match fs::read(&full_path) {
+                    Ok(contents) => contents,
+                    Err(_) => { summary.skipped = summary.skipped + 1; continue; }
+                };
+                summary.read = summary.read + 1;
+
+                let text: bool = std::str::from_utf8(&contents).is_ok();
+                if text == true { fresh_trigrams.push((new_id, trigrams_of(&contents))); }
+                files.push(IndexedFile { path: relative_path, size, modified, text });
+            }
+
+            let kept_paths: BTreeSet<&str> = files.iter().map(|file| file.path.as_str()).collect();
+            summary.removed = self.files.iter().filter(|file| kept_paths.contains(file.path.as_str()) == false).count();
+
+            let mut postings: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+            for (trigram, old_list) in &self.postings { // The new IDs are in the same path order as the old ones, so the carried over lists stay sorted.
+                let new_list: Vec<u32> = old_list.iter().filter_map(|old_id| old_to_new[*old_id as usize]).collect();
+                if new_list.is_empty() == false { postings.insert(*trigram, new_list); }
+            }
+            for (new_id, trigrams) in fresh_trigrams {
+                for trigram in trigrams { postings.entry(trigram).or_default().push(new_id); }
+            }
+            for list in postings.values_mut() { list.sort_unstable(); }
+
+            self.files = files;
+            self.postings = postings;
+            return summary;
+        }
+
+        pub fn save(&self) -> io::Result<PathBuf> { // Written to a temporary file first and renamed, so a search that runs at the same time never reads half an index.
+            let location: PathBuf = cache_location("trigrams", &self.root)?;
+            if let Some(directory) = location.parent() { fs::create_dir_all(directory)?; }
+
+            let temporary_path: PathBuf = location.with_extension(format!("{}.tmp", process::id()));
+            let written: io::Result<()> = (|| {
+                let mut temporary_file: fs::File = fs::File::create(&temporary_path)?;
+                temporary_file.write_all(&self.encode())?;
+                temporary_file.sync_all()?;
+                return fs::rename(&temporary_path, &location);
+            })();
+
+            if written.is_err() { let _ = fs::remove_file(&temporary_path); }
+            return written.map(|_| location);
+        }
+
+        pub fn candidates(&self, borrow_patterns: &[String], borrow_regex: bool) -> Vec<usize> { // The IDs of the text files that can contain a match. A pattern shorter than three bytes, or a regular expression, has no trigrams to narrow with, so every text file is a candidate.
+            let text_files: Vec<usize> = (0..self.files.len()).filter(|id| self.files[*id].text == true).collect();
+            if borrow_regex == true || borrow_patterns.iter().any(|pattern| pattern.len() < 3) { return text_files; }
+
+            let mut candidates: BTreeSet<u32> = BTreeSet::new();
+            for pattern in borrow_patterns { // A file is a candidate if it has every trigram of any one pattern.
+                let mut lists: Vec<&Vec<u32>> = Vec::new();
+                let mut every_trigram_found: bool = true;
+
+                for trigram in trigrams_of(pattern.as_bytes()) {
+                    match self.postings.get(&trigram) {
+                        Some(list) => lists.push(list),
+                        None => { every_trigram_found = false; break; }
+                    }
+                }
+                if every_trigram_found == false { continue; }
+
+                lists.sort_by_key(|list| list.len()); // The shortest list is the most selective, every other list only has to be checked against it.
+                for id in lists[0] {
+                    if lists[1..].iter().all(|list| list.binary_search(id).is_ok()) { candidates.insert(*id); }
+                }
+            }
+
+            return candidates.into_iter().map(|id| id as usize).collect();
+        }
+
+        fn encode(&self) -> Vec<u8> { // MAGIC, the root, the file table, then every trigram with its posting list. Numbers are varints and the sorted lists are stored as differences, which keeps the index a fraction of the size of the files.
+            let mut encoded: Vec<u8> = MAGIC.to_vec();
+            write_bytes(&mut encoded, self.root.to_string_lossy().as_bytes());
+
+            write_varint(&mut encoded, self.files.len() as u64);
+            for file in &self.files {
+                write_bytes(&mut encoded, file.path.as_bytes());
+                write_varint(&mut encoded, file.size);
+                write_varint(&mut encoded, file.modified);
+                encoded.push(file.text as u8);
+            }
+
+            write_varint(&mut encoded, self.postings.len() as u64);
+            let mut previous_trigram: u32 = 0;
+            for (trigram, list) in &self.postings {
+                write_varint(&mut encoded, (trigram - previous_trigram) as u64);
+                previous_trigram = *trigram;
+
+                write_varint(&mut encoded, list.len() as u64);
+                let mut previous_id: u32 = 0;
+                for id in list {
+                    write_varint(&mut encoded, (id - previous_id) as u64);
+                    previous_id = *id;
+                }
+            }
+
+            return encoded;
+        }
+    }
+
+    fn decode_index(borrow_contents: &[u8]) -> Option<TrigramIndex> { // None if the contents are not a complete index of this version.
+        if borrow_contents.starts_with(MAGIC) == false { return None; }
+        let mut position: usize = MAGIC.len();
+
+        let root: PathBuf = PathBuf::from(String::from_utf8(read_bytes(borrow_contents, &mut position)?.to_vec()).ok()?);
+
+        let file_count: usize = read_varint(borrow_contents, &mut position)? as usize;
+        let mut files: Vec<IndexedFile> = Vec::with_capacity(file_count.min(borrow_contents.len()));
+        for _ in 0..file_count {
+            let path: String = String::from_utf8(read_bytes(borrow_contents, &mut position)?.to_vec()).ok()?;
+            let size: u64 = read_varint(borrow_contents, &mut position)?;
+            let modified: u64 = read_varint(borrow_contents, &mut position)?;
+            let text: bool = *borrow_contents.get(position)? == 1;
+            position = position + 1;
+            files.push(IndexedFile { path, size, modified, text });
+        }
+
+        let trigram_count: usize = read_varint(borrow_contents, &mut position)? as usize;
+        let mut postings: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        let mut trigram: u32 = 0;
+        for _ in 0..trigram_count {
+            trigram = trigram.checked_add(u32::try_from(read_varint(borrow_contents, &mut position)?).ok()?)?;
+
+            let list_length: usize = read_varint(borrow_contents, &mut position)? as usize;
+            let mut list: Vec<u32> = Vec::with_capacity(list_length.min(file_count));
+            let mut id: u32 = 0;
+            for _ in 0..list_length {
+                id = id.checked_add(u32::try_from(read_varint(borrow_contents, &mut position)?).ok()?)?;
+                if id as usize >= file_count { return None; }
+                list.push(id);
+            }
+            postings.insert(trigram, list);
+        }
+
+        if position != borrow_contents.len() { return None; }
+        return Some(TrigramIndex { root, files, postings });
+    }
+
+    fn trigrams_of(borrow_bytes: &[u8]) -> Vec<u32> { // Every distinct run of three bytes, sorted.
+        let mut trigrams: Vec<u32> = borrow_bytes.windows(3).map(|window| (window[0] as u32) << 16 | (window[1] as u32) << 8 | window[2] as u32).collect();
+        trigrams.sort_unstable();
+        trigrams.dedup();
+        return trigrams;
+    }
+
+    pub fn write_varint(borrow_encoded: &mut Vec<u8>, borrow_value: u64) { // Seven bits per byte, the high bit says another byte follows.
+        let mut value: u64 = borrow_value;
+        while value >= 0x80 {
+            borrow_encoded.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        borrow_encoded.push(value as u8);
+    }
+
+    pub fn read_varint(borrow_contents: &[u8], borrow_position: &mut usize) -> Option<u64> {
+        let mut value: u64 = 0;
+        let mut shift: u32 = 0;
+
+        loop {
+            let byte: u8 = *borrow_contents.get(*borrow_position)?;
+            *borrow_position = *borrow_position + 1;
+            if shift > 63 { return None; }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 { return Some(value); }
+            shift = shift + 7;
+        }
+    }
+
+    pub fn write_bytes(borrow_encoded: &mut Vec<u8>, borrow_bytes: &[u8]) { // The length first, then the bytes.
+        write_varint(borrow_encoded, borrow_bytes.len() as u64);
+        borrow_encoded.extend_from_slice(borrow_bytes);
+    }
+
+    pub fn read_bytes<'a>(borrow_contents: &'a [u8], borrow_position: &mut usize) -> Option<&'a [u8]> {
+        let length: usize = read_varint(borrow_contents, borrow_position)? as usize;
+        let bytes: &'a [u8] = borrow_contents.get(*borrow_position..borrow_position.checked_add(length)?)?;
+        *borrow_position = *borrow_position + length;
+        return Some(bytes);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        type DecodedIndex = (PathBuf, Vec<(String, u64, u64, bool)>, BTreeMap<u32, Vec<u32>>);
+
+        fn sample_index() -> TrigramIndex {
+            let files: Vec<IndexedFile> = vec![
+                IndexedFile { path: "a.rs".to_string(), size: 0, modified: 0, text: true },
+                IndexedFile { path: "bild/größe.bin".to_string(), size: u64::MAX, modified: 1_700_000_000_123_456_789, text: false },
+                IndexedFile { path: "z".repeat(300), size: 128, modified: 127, text: true },
+            ];
+            let postings: BTreeMap<u32, Vec<u32>> = BTreeMap::from([(0, vec![0]), (0x616263, vec![0, 2]), (0xffffff, vec![2])]);
+            return TrigramIndex { root: PathBuf::from("/home/user/src"), files, postings };
+        }
+
+        fn contents_of(borrow_index: &TrigramIndex) -> DecodedIndex { // TrigramIndex has no PartialEq, so the fields are compared.
+            return (borrow_index.root.clone(), borrow_index.files.iter().map(|file| (file.path.clone(), file.size, file.modified, file.text)).collect(), borrow_index.postings.clone());
+        }
+
+        #[test]
+        fn varint_round_trip() {
+            for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+                let mut encoded: Vec<u8> = Vec::new();
+                write_varint(&mut encoded, value);
+                let mut position: usize = 0;
+                assert_eq!(read_varint(&encoded, &mut position), Some(value));
+                assert_eq!(position, encoded.len());
+            }
+        }
+
+        #[test]
+        fn varint_that_does_not_end_is_rejected() {
+            assert_eq!(read_varint(&[0x80, 0x80], &mut 0), None);
+            assert_eq!(read_varint(&[0xff; 11], &mut 0), None);
+            assert_eq!(read_bytes(&[5, b'a', b'b'], &mut 0), None);
+        }
+
+        #[test]
+        fn index_round_trip() {
+            let index: TrigramIndex = sample_index();
+            let decoded: TrigramIndex = decode_index(&index.encode()).expect("a complete index");
+            assert_eq!(contents_of(&decoded), contents_of(&index));
+
+            let empty: TrigramIndex = TrigramIndex::empty(PathBuf::from("/"));
+            assert_eq!(contents_of(&decode_index(&empty.encode()).expect("a complete index")), contents_of(&empty));
+        }
+
+        #[test]
+        fn truncated_index_is_rejected() {
+            let encoded: Vec<u8> = sample_index().encode();
+            for length in 0..encoded.len() { assert!(decode_index(&encoded[..length]).is_none(), "{} of {} bytes", length, encoded.len()); }
+        }
+
+        #[test]
+        fn corrupt_index_is_rejected() {
+            let encoded: Vec<u8> = sample_index().encode();
+
+            let mut other_version: Vec<u8> = encoded.clone();
+            other_version[7] = b'2';
+            assert!(decode_index(&other_version).is_none());
+
+            let mut trailing_byte: Vec<u8> = encoded.clone();
+            trailing_byte.push(0);
+            assert!(decode_index(&trailing_byte).is_none());
+
+            let mut unknown_file: TrigramIndex = sample_index(); // A posting list that names a file that is not in the file table.
+            unknown_file.postings.insert(7, vec![3]);
+            assert!(decode_index(&unknown_file.encode()).is_none());
+
+            let mut huge_delta: Vec<u8> = MAGIC.to_vec(); // A trigram difference that does not fit in 32 bits.
+            write_bytes(&mut huge_delta, b"/");
+            write_varint(&mut huge_delta, 0);
+            write_varint(&mut huge_delta, 1);
+            write_varint(&mut huge_delta, 1 << 32);
+            write_varint(&mut huge_delta, 0);
+            assert!(decode_index(&huge_delta).is_none());
+        }
+    }
+}
+
+pub mod locate_file_names { // Everything for the updatedb command and the locate option goes here. updatedb saves every path under a directory once, and find --locate reads the saved paths instead of walking the directory again.
+    use std::fs;
+    use std::io;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use walkdir::WalkDir;
+    use crate::filter_by_metadata::parse_duration;
+    use crate::index_file_contents::{cache_location, read_bytes, read_varint, write_bytes, write_varint};
+
+    const MAGIC: &[u8; 8] = b"LOZFDB01"; // The last two digits are the format version.
+    pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24); // Like a daily updatedb cron job.
+
+    pub struct FileNameDatabase {
+        pub root: PathBuf, // The canonical path of the directory that was saved.
+        pub created: SystemTime,
+        pub entries: Vec<(String, bool)>, // (path relative to the root, true if it is a directory). Sorted, the root itself is the empty path.
+    }
+
+    pub fn parse_max_age(borrow_value: &String) -> Duration { // 30m, 12h, 7d. Exits the process if the value is not a duration.
+        match parse_duration(borrow_value) {
+            Some(duration) => { return duration; }
+            None => {
+                print_line!("Invalid syntax. The max-age (--max-age, -ma) value is not a duration: {}. Use a number with s, m, h, d or w: 12h. Use \"lozgrep help find\" to see the options of the find command.", borrow_value);
+                process::exit(1);
+            }
+        }
+    }
+
+    pub fn build_database(borrow_root: &Path, borrow_follow: bool) -> (FileNameDatabase, usize) { // Returns the database, and how many entries could not be read.
+        let mut entries: Vec<(String, bool)> = Vec::new();
+        let mut skipped: usize = 0;
+
+        for entry_result in WalkDir::new(borrow_root).follow_links(borrow_follow) {
+            match entry_result {
+                Ok(entry) => {
+                    let relative_path: String = entry.path().strip_prefix(borrow_root).unwrap_or(entry.path()).to_string_lossy().into_owned();
+                    entries.push((relative_path, entry.file_type().is_dir()));
+                }
+                Err(_) => { skipped = skipped + 1; }
+            }
+        }
+
+        entries.sort(); // Neighbours share the longest prefixes when they are sorted, which is what front coding saves.
+        return (FileNameDatabase { root: borrow_root.to_path_buf(), created: SystemTime::now(), entries }, skipped);
+    }
+
+    pub fn find_database(borrow_path: &Path) -> io::Result<Option<(FileNameDatabase, String)>> { // The database of the path, or of the closest directory above it. Also returns where the path is inside of that database, "" if it is the root. Like locate, a database of / answers for every directory.
+        let mut root: Option<&Path> = Some(borrow_path);
+
+        while let Some(candidate) = root {
+            let location: PathBuf = cache_location("filenames", candidate)?;
+            match fs::read(&location) {
+                Ok(contents) => {
+                    let database: FileNameDatabase = decode_database(&contents).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("the filename database {} is damaged or was written by a different version, run lozgrep updatedb again", location.display())))?;
+                    let inside: String = borrow_path.strip_prefix(candidate).unwrap_or(Path::new("")).to_string_lossy().into_owned();
+                    return Ok(Some((database, inside)));
+                }
+                Err(error_one) if error_one.kind() == io::ErrorKind::NotFound => { root = candidate.parent(); }
+                Err(error_one) => { return Err(error_one); }
+            }
+        }
+
+        return Ok(None);
+    }
+
+    impl FileNameDatabase {
+        pub fn age(&self) -> Duration {
+            return SystemTime::now().duration_since(self.created).unwrap_or(Duration::ZERO); // A clock that went backwards counts as fresh.
+        }
+
+        pub fn entries_under<'a>(&'a self, borrow_inside: &'a str) -> impl Iterator<Item = (&'a str, bool)> + 'a { // The entries at or below borrow_inside, with borrow_inside taken off the front. The entries are sorted, so they are found with a binary search.
+            let first: usize = self.entries.partition_point(|(path, _)| path.as_str() < borrow_inside);
+
+            return self.entries[first..].iter().take_while(move |(path, _)| path.starts_with(borrow_inside)).filter_map(move |(path, is_directory)| { // Every path that starts with borrow_inside is next to each other, but src-old sorts between src and src/main.rs, so the / is checked on its own.
+                if borrow_inside.is_empty() { return Some((path.as_str(), *is_directory)); }
+                if path == borrow_inside { return Some(("", *is_directory)); }
+                return path[borrow_inside.len()..].strip_prefix('/').map(|rest| (rest, *is_directory));
+            });
+        }
+
+        pub fn save(&self) -> io::Result<PathBuf> { // Written to a temporary file first and renamed, so a find that runs at the same time never reads half a database.
+            let location: PathBuf = cache_location("filenames", &self.root)?;
+            if let Some(directory) = location.parent() { fs::create_dir_all(directory)?; }
+
+            let temporary_path: PathBuf = location.with_extension(format!("{}.tmp", process::id()));
+            let written: io::Result<()> = (|| {
+                let mut temporary_file: fs::File = fs::File::create(&temporary_path)?;
+                temporary_file.write_all(&self.encode())?;
+                temporary_file.sync_all()?;
+                return fs::rename(&temporary_path, &location);
+            })();
+
+            if written.is_err() { let _ = fs::remove_file(&temporary_path); }
+            return written.map(|_| location);
+        }
+
+        fn encode(&self) -> Vec<u8> { // Front coding like updatedb: every path only stores how many bytes it shares with the path before it, and the rest.
+            let mut encoded: Vec<u8> = MAGIC.to_vec();
+            write_bytes(&mut encoded, self.root.to_string_lossy().as_bytes());
+            write_varint(&mut encoded, self.created.duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0));
+            write_varint(&mut encoded, self.entries.len() as u64);
+
+            let mut previous: &[u8] = &[];
+            for (path, is_directory) in &self.entries {
+                let shared: usize = previous.iter().zip(path.as_bytes()).take_while(|(first, second)| first == second).count();
+                write_varint(&mut encoded, shared as u64);
+                write_bytes(&mut encoded, &path.as_bytes()[shared..]);
+                encoded.push(*is_directory as u8);
+                previous = path.as_bytes();
+            }
+
+            return encoded;
+        }
+    }
+
+    fn decode_database(borrow_contents: &[u8]) -> Option<FileNameDatabase> { // None if the contents are not a complete database of this version.
+        if borrow_contents.starts_with(MAGIC) == false { return None; }
+        let mut position: usize = MAGIC.len();
+
+        let root: PathBuf = PathBuf::from(String::from_utf8(read_bytes(borrow_contents, &mut position)?.to_vec()).ok()?);
+        let created: SystemTime = UNIX_EPOCH.checked_add(Duration::from_secs(read_varint(borrow_contents, &mut position)?))?;
+        let entry_count: usize = read_varint(borrow_contents, &mut position)? as usize;
+
+        let mut entries: Vec<(String, bool)> = Vec::with_capacity(entry_count.min(borrow_contents.len()));
+        let mut previous: Vec<u8> = Vec::new();
+        for _ in 0..entry_count {
+            let shared: usize = read_varint(borrow_contents, &mut position)? as usize;
+            if shared > previous.len() { return None; }
+            previous.truncate(shared);
+            previous.extend_from_slice(read_bytes(borrow_contents, &mut position)?);
+
+            let is_directory: bool = *borrow_contents.get(position)? == 1;
+            position = position + 1;
+            entries.push((String::from_utf8(previous.clone()).ok()?, is_directory));
+        }
+
+        if position != borrow_contents.len() { return None; }
+        return Some(FileNameDatabase { root, created, entries });
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn sample_database() -> FileNameDatabase {
+            let entries: Vec<(String, bool)> = vec![("".to_string(), true), ("src".to_string(), true), ("src-old".to_string(), true), ("src/größe.rs".to_string(), false), ("src/main.rs".to_string(), false)]; // Sorted, like build_database leaves them.
+            return FileNameDatabase { root: PathBuf::from("/home/user/project"), created: UNIX_EPOCH + Duration::from_secs(1_700_000_000), entries };
+        }
+
+        #[test]
+        fn database_round_trip() {
+            let database: FileNameDatabase = sample_database();
+            let decoded: FileNameDatabase = decode_database(&database.encode()).expect("a complete database");
+            assert_eq!((decoded.root, decoded.created, decoded.entries), (database.root, database.created, database.entries));
+        }
+
+        #[test]
+        fn truncated_database_is_rejected() {
+            let encoded: Vec<u8> = sample_database().encode();
+            for length in 0..encoded.len() { assert!(decode_database(&encoded[..length]).is_none(), "{} of {} bytes", length, encoded.len()); }
+        }
+
+        #[test]
+        fn corrupt_database_is_rejected() {
+            let mut trailing_byte: Vec<u8> = sample_database().encode();
+            trailing_byte.push(0);
+            assert!(decode_database(&trailing_byte).is_none());
+
+            let mut shares_too_much: Vec<u8> = MAGIC.to_vec(); // The first path cannot share bytes with the path before it.
+            write_bytes(&mut shares_too_much, b"/");
+            write_varint(&mut shares_too_much, 0);
+            write_varint(&mut shares_too_much, 1);
+            write_varint(&mut shares_too_much, 2);
+            write_bytes(&mut shares_too_much, b"a");
+            shares_too_much.push(0);
+            assert!(decode_database(&shares_too_much).is_none());
+
+            let mut split_character: Vec<u8> = MAGIC.to_vec(); // Front coding that cuts a UTF-8 character in two.
+            write_bytes(&mut split_character, b"/");
+            write_varint(&mut split_character, 0);
+            write_varint(&mut split_character, 1);
+            write_varint(&mut split_character, 0);
+            write_bytes(&mut split_character, &"ß".as_bytes()[..1]);
+            split_character.push(0);
+            assert!(decode_database(&split_character).is_none());
+        }
+
+        #[test]
+        fn entries_under_a_directory() {
+            let database: FileNameDatabase = sample_database();
+            assert_eq!(database.entries_under("src").collect::<Vec<(&str, bool)>>(), vec![("", true), ("größe.rs", false), ("main.rs", false)]);
+            assert_eq!(database.entries_under("").count(), 5);
+        }
+    }
+}
+
+pub mod replace_in_files { // Everything for the replace command that is not searching goes here. The changed lines are printed as a unified diff, or written back to the file.
+    use std::fs;
+    use std::io;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::process;
+
+    const CONTEXT_LINES: usize = 3; // Same as diff -u, so patch can apply the output.
+
+    struct ChangedBlock { // Whole lines of the file that hold at least one replacement. old_start and old_end are byte offsets, new_text is the same lines after the replacement.
+        old_start: usize,
+        old_end: usize,
+        new_text: String,
+    }
+
+    pub fn replace_all(borrow_buffer: &str, borrow_replacements: &[(usize, usize, String)]) -> String { // The buffer with every (start, end, text) replaced. The replacements have to be sorted and must not overlap, PatternMatcher::replacements returns them that way.
+        let mut replaced: String = String::with_capacity(borrow_buffer.len());
+        let mut copied_until: usize = 0;
+
+        for (start, end, text) in borrow_replacements {
+            replaced.push_str(&borrow_buffer[copied_until..*start]);
+            replaced.push_str(text);
+            copied_until = *end;
+        }
+
+        replaced.push_str(&borrow_buffer[copied_until..]);
+        return replaced;
+    }
+
+    fn changed_blocks(borrow_buffer: &str, borrow_replacements: &[(usize, usize, String)]) -> Vec<ChangedBlock> { // Replacements on the same line end up in the same block. If a replacement removes a line break, the next line is joined to the block, so every block ends at the end of a line in both files.
+        let bytes: &[u8] = borrow_buffer.as_bytes();
+        let line_end_after = |position: usize| -> usize { memchr::memchr(b'\n', &bytes[position.min(bytes.len())..]).map(|index| position + index + 1).unwrap_or(bytes.len()) };
+        let mut ranges: Vec<(usize, usize, Vec<usize>)> = Vec::new(); // (old_start, old_end, indexes of the replacements inside). Changed lines next to each other are one block, so diff prints all their old lines before their new lines.
+
+        for (index, (start, end, _)) in borrow_replacements.iter().enumerate() {
+            let line_start: usize = memchr::memrchr(b'\n', &bytes[..*start]).map(|newline| newline + 1).unwrap_or(0);
+            let line_end: usize = line_end_after(if end > start { end - 1 } else { *start }); // The line that holds the last replaced byte.
+
+            match ranges.last_mut() {
+                Some(range) if line_start <= range.1 => { range.1 = range.1.max(line_end); range.2.push(index); }
+                _ => ranges.push((line_start, line_end, vec![index])),
+            }
+        }
+
+        let mut blocks: Vec<ChangedBlock> = Vec::new();
+        let mut range_index: usize = 0;
+
+        while range_index < ranges.len() {
+            let (old_start, mut old_end, mut members) = ranges[range_index].clone();
+            range_index = range_index + 1;
+
+            loop {
+                let new_text: String = replace_block(borrow_buffer, old_start, old_end, &members, borrow_replacements);
+
+                if new_text.ends_with('\n') || new_text.is_empty() || old_end == bytes.len() { // The block ends with a whole line, or the file ends here.
+                    blocks.push(ChangedBlock { old_start, old_end, new_text });
+                    break;
+                }
+
+                old_end = line_end_after(old_end); // The last line lost its line break, so the next line is part of the same changed line now.
+                while range_index < ranges.len() && ranges[range_index].0 <= old_end {
+                    old_end = old_end.max(ranges[range_index].1);
+                    members.extend(ranges[range_index].2.iter().copied());
+                    range_index = range_index + 1;
+                }
+            }
+        }
+
+        return blocks;
+    }
+
+    fn replace_block(borrow_buffer: &str, borrow_old_start: usize, borrow_old_end: usize, borrow_members: &[usize], borrow_replacements: &[(usize, usize, String)]) -> String { // The text of one block after its replacements.
+        let shifted: Vec<(usize, usize, String)> = borrow_members.iter().map(|index| &borrow_replacements[*index]).map(|(start, end, text)| (start - borrow_old_start, end - borrow_old_start, text.clone())).collect();
+        return replace_all(&borrow_buffer[borrow_old_start..borrow_old_end], &shifted);
+    }
+
+    pub fn print_unified_diff(borrow_path: &str, borrow_buffer: &str, borrow_replacements: &[(usize, usize, String)]) { // Prints the changes like diff -u, so the output can be reviewed and then applied with patch -p0.
+        for line in unified_diff(borrow_path, borrow_buffer, borrow_replacements) { print_line!("{}", line); }
+    }
+
+    fn unified_diff(borrow_path: &str, borrow_buffer: &str, borrow_replacements: &[(usize, usize, String)]) -> Vec<String> { // The lines print_unified_diff prints, without their line breaks.
+        let old_lines: Vec<&str> = borrow_buffer.split_inclusive('\n').collect(); // Every line keeps its line break, so a last line without one can be marked.
+        let mut line_starts: Vec<usize> = Vec::with_capacity(old_lines.len());
+        let mut offset: usize = 0;
+        for line in &old_lines { line_starts.push(offset); offset = offset + line.len(); }
+        let line_index = |byte_offset: usize| -> usize { line_starts.partition_point(|start| *start <= byte_offset).saturating_sub(1) };
+
+        let changed: Vec<ChangedBlock> = changed_blocks(borrow_buffer, borrow_replacements);
+        let mut blocks: Vec<(usize, usize, Vec<&str>)> = Vec::new(); // (first old line, old line count, new lines)
+        for block in &changed {
+            let first_line: usize = if block.old_start == borrow_buffer.len() { old_lines.len() } else { line_index(block.old_start) };
+            let line_count: usize = if block.old_end == block.old_start { 0 } else { line_index(block.old_end - 1) + 1 - first_line };
+            blocks.push((first_line, line_count, block.new_text.split_inclusive('\n').collect()));
+        }
+
+        let mut diff_lines: Vec<String> = vec![format!("--- {}", borrow_path), format!("+++ {}", borrow_path)];
+
+        let mut line_shift: isize = 0; // How many lines the new file is ahead of the old file, before the current hunk.
+        let mut block_index: usize = 0;
+
+        while block_index < blocks.len() { // Blocks that are close enough to share their context lines are printed as one hunk.
+            let mut last_block: usize = block_index;
+            while last_block + 1 < blocks.len() && blocks[last_block + 1].0 <= blocks[last_block].0 + blocks[last_block].1 + 2 * CONTEXT_LINES { last_block = last_block + 1; }
+
+            let hunk_start: usize = blocks[block_index].0.saturating_sub(CONTEXT_LINES);
+            let hunk_end: usize = (blocks[last_block].0 + blocks[last_block].1 + CONTEXT_LINES).min(old_lines.len());
+            let new_count: usize = (hunk_end - hunk_start) - blocks[block_index..=last_block].iter().map(|block| block.1).sum::<usize>() + blocks[block_index..=last_block].iter().map(|block| block.2.len()).sum::<usize>();
+            let old_count: usize = hunk_end - hunk_start;
+            let new_start: usize = (hunk_start as isize + line_shift) as usize;
+
+            diff_lines.push(format!("@@ -{} +{} @@", hunk_range(hunk_start, old_count), hunk_range(new_start, new_count)));
+
+            let mut line: usize = hunk_start;
+            for (first_line, line_count, new_lines) in &blocks[block_index..=last_block] {
+                while line < *first_line { push_diff_line(&mut diff_lines, ' ', old_lines[line]); line = line + 1; }
+                for old_line in &old_lines[*first_line..*first_line + *line_count] { push_diff_line(&mut diff_lines, '-', old_line); }
+                for new_line in new_lines { push_diff_line(&mut diff_lines, '+', new_line); }
+                line = first_line + line_count;
+                line_shift = line_shift + new_lines.len() as isize - *line_count as isize;
+            }
+            while line < hunk_end { push_diff_line(&mut diff_lines, ' ', old_lines[line]); line = line + 1; }
+
+            block_index = last_block + 1;
+        }
+
+        return diff_lines;
+    }
+
+    fn hunk_range(borrow_start: usize, borrow_count: usize) -> String { // Line numbers start at 1. An empty range names the line before it, like diff does.
+        if borrow_count == 1 { return format!("{}", borrow_start + 1); }
+        if borrow_count == 0 { return format!("{},0", borrow_start); }
+        return format!("{},{}", borrow_start + 1, borrow_count);
+    }
+
+    fn push_diff_line(borrow_diff_lines: &mut Vec<String>, borrow_marker: char, borrow_line: &str) {
+        match borrow_line.strip_suffix('\n') {
+            Some(line) => borrow_diff_lines.push(format!("{}{}", borrow_marker, line)),
+            None => { borrow_diff_lines.push(format!("{}{}", borrow_marker, borrow_line)); borrow_diff_lines.push("\\ No newline at end of file".to_string()); }
+        }
+    }
+
+    pub fn write_file_atomically(borrow_path: &Path, borrow_contents: &str) -> io::Result<()> { // The new contents are written to a temporary file next to the original, which is then renamed over it. A crash or a full disk leaves the original file as it was, never half written.
+        let directory: &Path = match borrow_path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() == false => parent,
+            _ => Path::new("."),
+        };
+        let file_name: String = borrow_path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let temporary_path: PathBuf = directory.join(format!(".{}.lozgrep-{}.tmp", file_name, process::id())); // Same directory, so the rename does not cross file systems.
+        let permissions: fs::Permissions = fs::metadata(borrow_path)?.permissions();
+
+        let written: io::Result<()> = (|| {
+            let mut temporary_file: fs::File = fs::OpenOptions::new().write(true).create_new(true).open(&temporary_path)?; // create_new fails instead of writing through a file or link that is already there.
+            temporary_file.write_all(borrow_contents.as_bytes())?;
+            temporary_file.set_permissions(permissions)?; // The rewritten file keeps the mode of the original, a script stays executable.
+            temporary_file.sync_all()?;
+            return fs::rename(&temporary_path, borrow_path);
+        })();
+
+        if written.is_err() { let _ = fs::remove_file(&temporary_path); } // The temporary file is cleaned up, the error is still returned.
+        return written;
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::match_many_patterns::PatternMatcher;
+
+        fn replacements_of(borrow_patterns: &[&str], borrow_buffer: &str, borrow_replacement: &str) -> Vec<(usize, usize, String)> {
+            return PatternMatcher::build(borrow_patterns.iter().map(|pattern| pattern.to_string()).collect()).replacements(borrow_buffer, borrow_replacement);
+        }
+
+        fn blocks_of(borrow_patterns: &[&str], borrow_buffer: &str, borrow_replacement: &str) -> Vec<(usize, usize, String)> { // (old_start, old_end, new_text) of every changed block.
+            return changed_blocks(borrow_buffer, &replacements_of(borrow_patterns, borrow_buffer, borrow_replacement)).into_iter().map(|block| (block.old_start, block.old_end, block.new_text)).collect();
+        }
+
+        fn diff_of(borrow_patterns: &[&str], borrow_buffer: &str, borrow_replacement: &str) -> Vec<String> {
+            return unified_diff("file", borrow_buffer, &replacements_of(borrow_patterns, borrow_buffer, borrow_replacement));
+        }
+
+        #[test]
+        fn changed_lines_next_to_each_other_are_one_block() {
+            assert_eq!(blocks_of(&["x"], "x x\ny\n", "z"), vec![(0, 4, "z z\n".to_string())]);
+            assert_eq!(blocks_of(&["x"], "x\nx\ny\nx\n", "z"), vec![(0, 4, "z\nz\n".to_string()), (6, 8, "z\n".to_string())]);
+        }
+
+        #[test]
+        fn removed_line_break_joins_the_next_line() {
+            assert_eq!(blocks_of(&["a\n"], "a\nb\nc\n", "a"), vec![(0, 4, "ab\n".to_string())]);
+            assert_eq!(blocks_of(&["\n"], "a\nb", ""), vec![(0, 3, "ab".to_string())]);
+        }
+
+        #[test]
+        fn diff_of_one_change_has_three_lines_of_context() {
+            assert_eq!(diff_of(&["e"], "a\nb\nc\nd\ne\nf\ng\nh\ni\n", "E"), vec!["--- file", "+++ file", "@@ -2,7 +2,7 @@", " b", " c", " d", "-e", "+E", " f", " g", " h"]);
+        }
+
+        #[test]
+        fn diff_merges_close_changes_into_one_hunk() {
+            let buffer: &str = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+            assert_eq!(diff_of(&["b", "h"], buffer, "X"), vec!["--- file", "+++ file", "@@ -1,11 +1,11 @@", " a", "-b", "+X", " c", " d", " e", " f", " g", "-h", "+X", " i", " j", " k"]);
+            assert_eq!(diff_of(&["b", "k"], buffer, "X"), vec!["--- file", "+++ file", "@@ -1,5 +1,5 @@", " a", "-b", "+X", " c", " d", " e", "@@ -8,5 +8,5 @@", " h", " i", " j", "-k", "+X", " l"]);
+        }
+
+        #[test]
+        fn diff_counts_added_and_removed_lines() {
+            assert_eq!(diff_of(&["b"], "a\nb\nc\n", "b1\nb2"), vec!["--- file", "+++ file", "@@ -1,3 +1,4 @@", " a", "-b", "+b1", "+b2", " c"]);
+            assert_eq!(diff_of(&["a\n"], "a\nb\nc\n", "a"), vec!["--- file", "+++ file", "@@ -1,3 +1,2 @@", "-a", "-b", "+ab", " c"]);
+        }
+
+        #[test]
+        fn diff_marks_a_missing_line_break_at_the_end() {
+            assert_eq!(diff_of(&["c"], "a\nb\nc", "d"), vec!["--- file", "+++ file", "@@ -1,3 +1,3 @@", " a", " b", "-c", "\\ No newline at end of file", "+d", "\\ No newline at end of file"]);
+            assert_eq!(diff_of(&["c\n"], "a\nc\n", "c"), vec!["--- file", "+++ file", "@@ -1,2 +1,2 @@", " a", "-c", "+c", "\\ No newline at end of file"]);
+        }
+    }
+}
+
+pub mod watch_for_changes { // Everything for the watch option goes here. A file is followed like tail -f, a directory is watched and every file that changes in it is followed from then on.
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::io;
+    use std::io::{Read, Seek, SeekFrom};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    pub const POLL_INTERVAL: Duration = Duration::from_secs(1); // The followed files are checked at least this often, so a missed event or a network file system only delays the new lines.
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum FollowChange {
+        Truncated, // The file got shorter, it is read again from the start.
+        Replaced, // A different file is at the path now, logrotate moved the old one away. The new file is read from the start.
+        Gone, // Nothing is at the path, the file is read from the start when it comes back.
+    }
+
+    pub struct FollowUpdate {
+        pub lines: String, // The complete lines that were appended since the last read, with their line breaks. A line without a line break yet is kept until it is finished.
+        pub change: Option<FollowChange>,
+    }
+
+    pub struct FollowedFile {
+        pub path: String,
+        offset: u64, // How many bytes of the file were read.
+        identity: Option<u64>, // The inode of the file that was read, None while nothing is at the path.
+        pending: Vec<u8>, // The start of a line that is still being written.
+    }
+
+    pub struct WatchChanges {
+        pub paths: BTreeSet<PathBuf>, // The files that were created or written to, every file only once.
+        pub errors: Vec<String>,
+    }
+
+    impl FollowedFile {
+        pub fn new(borrow_path: &str) -> FollowedFile { // The file is read from the start on the first read, so the lines that are already there are searched too.
+            let identity: Option<u64> = fs::metadata(borrow_path).ok().map(|metadata| file_identity(&metadata));
+            return FollowedFile { path: borrow_path.to_string(), offset: 0, identity, pending: Vec::new() };
+        }
+
+        pub fn read_new_lines(&mut self) -> io::Result<FollowUpdate> { // Reads what was appended since the last call. Truncation and rotation are found by comparing the size and the inode with the last read.
+            let metadata: fs::Metadata = match fs::metadata(&self.path) {
+                Ok(metadata) => metadata,
+                Err(error_one) if error_one.kind() == io::ErrorKind::NotFound => {
+                    let change: Option<FollowChange> = if self.identity.is_some() { Some(FollowChange::Gone) } else { None }; // Only reported once, not on every check while the file is missing.
+                    self.identity = None;
+                    return Ok(FollowUpdate { lines: String::new(), change });
+                }
+                Err(error_one) => { return Err(error_one); }
+            };
+
+            let mut change: Option<FollowChange> = None;
+            let identity: u64 = file_identity(&metadata);
+
+            if self.identity != Some(identity) {
+                if self.identity.is_some() || self.offset > 0 { change = Some(FollowChange::Replaced); }
+                self.identity = Some(identity);
+                self.offset = 0;
+                self.pending.clear();
+            } else if metadata.len() < self.offset {
+                change = Some(FollowChange::Truncated);
+                self.offset = 0;
+                self.pending.clear();
+            }
+
+            let mut file: fs::File = fs::File::open(&self.path)?;
+            file.seek(SeekFrom::Start(self.offset))?;
+            let read_length: usize = file.read_to_end(&mut self.pending)?;
+            self.offset = self.offset + read_length as u64;
+
+            let complete_length: usize = match self.pending.iter().rposition(|byte| *byte == b'\n') {
+                Some(position) => position + 1,
+                None => 0,
+            };
+            let complete_lines: Vec<u8> = self.pending.drain(..complete_length).collect();
+
+            return Ok(FollowUpdate { lines: String::from_utf8_lossy(&complete_lines).into_owned(), change }); // A log with a broken byte still has its other lines searched.
+        }
+    }
+
+    #[cfg(unix)]
+    fn file_identity(borrow_metadata: &fs::Metadata) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+        return borrow_metadata.ino();
+    }
+
+    #[cfg(not(unix))]
+    fn file_identity(_borrow_metadata: &fs::Metadata) -> u64 { // Inodes are not exposed on this platform, so a rotated file is only found if it is shorter than the old one.
+        return 0;
+    }
+
+    #[cfg(feature = "watch")]
+    pub struct ChangeWatcher {
+        watcher: notify::RecommendedWatcher,
+        receiver: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+    }
+
+    #[cfg(feature = "watch")]
+    impl ChangeWatcher {
+        pub fn new() -> io::Result<ChangeWatcher> {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let watcher: notify::RecommendedWatcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+            return Ok(ChangeWatcher { watcher, receiver });
+        }
+
+        pub fn watch(&mut self, borrow_path: &Path, borrow_recursive: bool) -> io::Result<()> {
+            use notify::Watcher;
+
+            let mode: notify::RecursiveMode = if borrow_recursive == true { notify::RecursiveMode::Recursive } else { notify::RecursiveMode::NonRecursive };
+            return self.watcher.watch(borrow_path, mode).map_err(io::Error::other);
+        }
+
+        pub fn next_changes(&self, borrow_timeout: Duration) -> WatchChanges { // Waits for the first change, then collects the changes that follow it for a moment, so a file that is written in many small pieces is only searched once.
+            use std::time::Instant;
+
+            let mut changes: WatchChanges = WatchChanges { paths: BTreeSet::new(), errors: Vec::new() };
+            let first_event = match self.receiver.recv_timeout(borrow_timeout) {
+                Ok(event) => event,
+                Err(_) => { return changes; }
+            };
+
+            let started: Instant = Instant::now();
+            let mut next_event = Some(first_event);
+
+            while let Some(event_result) = next_event {
+                match event_result {
+                    Ok(event) => {
+                        if event.kind.is_create() || event.kind.is_modify() { changes.paths.extend(event.paths); } // A removed file has nothing left to search.
+                    }
+                    Err(error_one) => { changes.errors.push(error_one.to_string()); }
+                }
+
+                if started.elapsed() > POLL_INTERVAL { break; } // A file that is written to all the time still gets its matches printed every second.
+                next_event = self.receiver.recv_timeout(Duration::from_millis(100)).ok();
+            }
+
+            return changes;
+        }
+    }
+
+    #[cfg(not(feature = "watch"))]
+    pub struct ChangeWatcher;
+
+    #[cfg(not(feature = "watch"))]
+    impl ChangeWatcher { // parse_and_build_arguments does not accept --watch in this build, this is only reached if it is called from somewhere else.
+        pub fn new() -> io::Result<ChangeWatcher> {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "lozgrep was built without the watch feature"));
+        }
+
+        pub fn watch(&mut self, _borrow_path: &Path, _borrow_recursive: bool) -> io::Result<()> {
+            return Ok(());
+        }
+
+        pub fn next_changes(&self, borrow_timeout: Duration) -> WatchChanges {
+            std::thread::sleep(borrow_timeout);
+            return WatchChanges { paths: BTreeSet::new(), errors: Vec::new() };
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::io::Write;
+
+        fn temp_file(borrow_name: &str, borrow_contents: &str) -> String { // Returns the path of a new file with the given contents.
+            let directory: PathBuf = std::env::temp_dir().join(format!("lozgrep-followed-files-{}", std::process::id()));
+            fs::create_dir_all(&directory).unwrap();
+            let path: PathBuf = directory.join(borrow_name);
+            fs::write(&path, borrow_contents).unwrap();
+            return path.to_string_lossy().to_string();
+        }
+
+        fn append(borrow_path: &str, borrow_contents: &str) {
+            fs::OpenOptions::new().append(true).open(borrow_path).unwrap().write_all(borrow_contents.as_bytes()).unwrap();
+        }
+
+        fn read(borrow_followed_file: &mut FollowedFile) -> (String, Option<FollowChange>) {
+            let update: FollowUpdate = borrow_followed_file.read_new_lines().unwrap();
+            return (update.lines, update.change);
+        }
+
+        #[test]
+        fn appended_lines_are_read_once() {
+            let path: String = temp_file("append.log", "one\n");
+            let mut followed_file: FollowedFile = FollowedFile::new(&path);
+
+            assert_eq!(read(&mut followed_file), ("one\n".to_string(), None));
+            assert_eq!(read(&mut followed_file), (String::new(), None));
+
+            append(&path, "two\nthr");
+            assert_eq!(read(&mut followed_file), ("two\n".to_string(), None)); // thr is not a complete line yet.
+            append(&path, "ee\n");
+            assert_eq!(read(&mut followed_file), ("three\n".to_string(), None));
+        }
+
+        #[test]
+        fn truncated_file_is_read_from_the_start() {
+            let path: String = temp_file("truncate.log", "one\ntwo\n");
+            let mut followed_file: FollowedFile = FollowedFile::new(&path);
+            read(&mut followed_file);
+
+            fs::OpenOptions::new().write(true).truncate(true).open(&path).unwrap().write_all(b"new\n").unwrap(); // Same inode, shorter than what was read.
+            assert_eq!(read(&mut followed_file), ("new\n".to_string(), Some(FollowChange::Truncated)));
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn rotated_file_is_read_from_the_start() {
+            let path: String = temp_file("rotate.log", "old one\nold two\n");
+            let mut followed_file: FollowedFile = FollowedFile::new(&path);
+            read(&mut followed_file);
+
+            fs::rename(&path, format!("{}.1", path)).unwrap(); // What logrotate does.
+            assert_eq!(read(&mut followed_file), (String::new(), Some(FollowChange::Gone)));
+            assert_eq!(read(&mut followed_file), (String::new(), None)); // Gone is only reported once.
+
+            fs::write(&path, "new one and more\n").unwrap(); // Longer than the old file, so only the inode shows it is a different file.
+            assert_eq!(read(&mut followed_file), ("new one and more\n".to_string(), Some(FollowChange::Replaced)));
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn file_replaced_between_reads_is_read_from_the_start() {
+            let path: String = temp_file("replace.log", "old\n");
+            let mut followed_file: FollowedFile = FollowedFile::new(&path);
+            read(&mut followed_file);
+
+            let new_path: String = temp_file("replace.log.new", "first\nsecond\n");
+            fs::rename(&new_path, &path).unwrap();
+            assert_eq!(read(&mut followed_file), ("first\nsecond\n".to_string(), Some(FollowChange::Replaced)));
+        }
+    }
+}
+
+pub mod print_to_terminal { // All print to terminal functions go here. The help menu, the man page and the shell completions are all built from OPTION_TABLE, so a new option only has to be added to the table.
+    use std::fmt;
+    use std::io;
+    use std::io::Write;
+    use std::process;
+    use crate::parse_and_build_arguments::{OptionScope, OptionSpec, OPTION_TABLE, COMMAND_TABLE};
+    use crate::filter_by_file_type::built_in_type_names;
+
+    const VERSION: &str = env!("CARGO_PKG_VERSION"); // From Cargo.toml, so the version cannot drift from the package.
+
+    const GREP_USAGE: &str = "lozgrep grep [OPTIONS] QUERY FILE...";
+    const FIND_USAGE: &str = "lozgrep find [OPTIONS] QUERY DIRECTORY...";
+    const REPLACE_USAGE: &str = "lozgrep replace [OPTIONS] QUERY REPLACEMENT FILE...";
+    const INDEX_USAGE: &str = "lozgrep index [OPTIONS] DIRECTORY...";
+    const UPDATEDB_USAGE: &str = "lozgrep updatedb [OPTIONS] DIRECTORY...";
+    const HELP_USAGE: &str = "lozgrep help [grep|find|replace|index|updatedb]";
+
+    const GREP_DESCRIPTION: [&str; 12] = [
+        "grep reads every file and prints the lines that contain the query.",
+        "-sg (--simple-grep) is the same as the grep command, it is kept for existing scripts.",
+        "The query is matched exactly, upper and lower case are different.",
+        "When more than one file is searched, the path is put in front of every line: path:line",
+        "A symbolic link named on the command line is read like the file it points to. Files that are not of the selected types are skipped.",
+        "With --search-zip, compressed files (.gz, .bz2, .xz, .zst) are decompressed first. The lines are printed with the path of the compressed file.",
+        "With --search-archives, every text member of a tar or zip file is searched and printed with its virtual path: release.tar.gz!/etc/app.conf:line",
+        "Prints \"No matches found.\" if no line contains the query.",
+        "With --multiline, a match can go over line breaks. Every line the match covers is printed with its line number: path:12:line. In a regular expression \\n matches the line break, . only matches it after (?s).",
+        "With --only-matching, every match is printed on its own line instead of the line it is on. --output prints a template for every match instead, filled in from the capture groups of --regex: $1, ${name}.",
+        "--max-count stops reading a file after a number of matching lines, --limit stops grep after a number of results over every file. --quiet prints nothing, not even the files that cannot be read, and stops at the first match. The exit code is 0 if there was one and 1 if not.",
+        "With --watch, grep keeps running. A file is searched and then followed like tail -f, a truncated or rotated file is followed from the start again. A directory can be passed too, a file that is created or changed in it is searched and then followed, so only its new lines are searched on the next change.",
+    ];
+
+    const FIND_DESCRIPTION: [&str; 9] = [
+        "find walks every directory and prints the files and directories whose name contains the query.",
+        "-sf (--simple-find) is the same as the find command, it is kept for existing scripts.",
+        "The query is matched exactly, upper and lower case are different.",
+        "Symbolic links to directories are only walked if --follow is passed. Symbolic link loops are reported and skipped.",
+        "The size, mtime, atime, user, group and perm options have to pass for an entry to be printed. The type options only match files.",
+        "With --search-archives, the members of tar and zip files are matched too, as archive!/member. The metadata options are checked against the archive.",
+        "Entries that cannot be read are printed as errors, unless quiet-errors, error-summary or list-errors is passed.",
+        "--limit stops the walk after a number of results. --quiet prints nothing, not even the entries that cannot be read, and stops the walk at the first match. The exit code is 0 if there was one and 1 if not.",
+        "With --locate, the paths are read from the filename database saved by updatedb instead of walking the directory. A warning is printed if the database is older than --max-age.",
+    ];
+
+    const REPLACE_DESCRIPTION: [&str; 6] = [
+        "replace finds the query in every file like grep, and replaces every match with the replacement.",
+        "By default nothing is changed, a unified diff of the changes is printed. It can be applied with: patch -p0",
+        "--in-place rewrites the files. The new contents are written to a temporary file next to the original, which is then renamed over it, so a file is never left half written. The permissions are kept.",
+        "With --regex the replacement can use the capture groups of the match: $1, ${1}, ${name}. $$ is a literal $. Use ${1}a instead of $1a, because $1a is read as the group named 1a.",
+        "A symbolic link named on the command line is followed. With --in-place the file it points to is rewritten, the link is kept.",
+        "-sr (--simple-replace) is the same as the replace command.",
+    ];
+
+    const INDEX_DESCRIPTION: [&str; 5] = [
+        "index reads every file in the directories and writes down which trigrams (three bytes in a row) every file contains.",
+        "grep --indexed then only reads the files that contain every trigram of the query. A query shorter than three bytes, or --regex, reads every text file.",
+        "Running index again only reads the files whose size or modification time changed. grep --indexed does the same before every search, so the index never hides a match.",
+        "The index is kept in $XDG_CACHE_HOME/lozgrep, or ~/.cache/lozgrep, not in the directory. .git directories and files that are not UTF-8 text are not indexed.",
+        "-si (--simple-index) is the same as the index command.",
+    ];
+
+    const UPDATEDB_DESCRIPTION: [&str; 5] = [
+        "updatedb walks every directory once and saves the path of every file and directory in it to the filename database.",
+        "find --locate then matches the query against the saved paths, without walking the directory. The database of a directory also answers for every directory below it.",
+        "The database is not updated on its own. Files created since updatedb ran are missing and deleted files are still printed, so find --locate warns when the database is older than --max-age, 1d by default.",
+        "The database is kept in $XDG_CACHE_HOME/lozgrep, or ~/.cache/lozgrep, next to the trigram index. Running updatedb again replaces it.",
+        "-su (--simple-updatedb) is the same as the updatedb command.",
+    ];
+
+    const SYNTAX_RULES: [&str; 14] = [
+        "The command is the first argument: lozgrep grep, lozgrep find, lozgrep replace, lozgrep index or lozgrep updatedb. -sg, -sf, -sr, -si and -su can be used instead, anywhere on the command line or in the config file.",
+        "There is no default command, it has to be sprecified on the command line or in the config file.",
+        "Every command has its own options. An option of another command is an error on the command line, and is ignored in the environment and the config file.",
+        "The options can come in any order.",
+        "The long option (--) or short option (-) can be used interchangeably.",
+        "An option that takes a value owns the argument after it, even if it starts with -: -q -5",
+        "The value can also be joined to the option: --query=foo or -qfoo. The rest of a short option is always its value, even if it looks like another option: -qtype is -q type.",
+        "Single letter options can be bundled: -vh is the same as -v -h",
+        "Values that do not follow an option are used as the query first and then the path: lozgrep grep wordiamlookingfor /home/user/file",
+        "With --patterns-file on the command line, every value that does not follow an option is a path: lozgrep grep -f patterns.txt src/*.rs",
+        "Every value after the query is another path to search: lozgrep grep -q wordiamlookingfor src/*.rs",
+        "Everything after -- is a value, even if it starts with -.",
+        "@file is replaced by the arguments inside of the file, one argument per line. If there is no such file, @file is a value. A value that starts with @ and is also a file name goes after --: lozgrep grep -- @Override src",
+        "Every unknown option is listed, with the closest known option if it looks like a typo: --follw (did you mean --follow?)",
+    ];
+
+    const CONFIG_FILE_RULES: [&str; 4] = [
+        "Default options are read from $XDG_CONFIG_HOME/lozgrep/config, or ~/.config/lozgrep/config if XDG_CONFIG_HOME is not set.",
+        "The LOZGREP_CONFIG environment variable can point to a different file.",
+        "The config file has one argument per line, like an @file. Lines that start with # are comments.",
+        "An option on the command line replaces the same option from the config file. The grep, find, replace, index and updatedb commands replace each other, so do quiet-errors, error-summary and list-errors.",
+    ];
+
+    const ENVIRONMENT_RULES: [&str; 4] = [
+        "Every option except help, version and the generators can be set with a LOZGREP_ variable named after the long option: LOZGREP_FOLLOW=1, LOZGREP_TYPE_NOT=json.",
+        "Options without a value take 1, true, yes or on. 0, false, no or off turns the option off if it is set in the config file.",
+        "The command line replaces the environment, the environment replaces the config file, the config file replaces the built-in defaults.",
+        "--verbose shows where every setting came from.",
+    ];
+
+    const GREP_EXAMPLES: [&str; 13] = [
+        "lozgrep grep -p /home/user/file -q wordiamlookingfor",
+        "lozgrep grep wordiamlookingfor src/*.rs",
+        "lozgrep grep -z error /var/log/syslog*",
+        "lozgrep grep --quiet -f leaked_keys.txt config/*.yml && echo found",
+        "lozgrep grep --only-matching --regex 'https?://[^ \"]+' docs/*.md",
+        "lozgrep grep -re -ot '${user} from ${ip}' 'Accepted password for (?P<user>\\S+) from (?P<ip>\\S+)' /var/log/auth.log",
+        "lozgrep grep --multiline --regex 'unsafe \\{\\n[^}]*transmute' src/*.rs",
+        "lozgrep grep --watch -q \"status=500\" /var/log/nginx/access.log /srv/app/logs",
+        "lozgrep grep -ar -t conf listen release.tar.gz",
+        "lozgrep grep -t rust wordiamlookingfor src/*",
+        "lozgrep grep -f forbidden_identifiers.txt src/*.rs",
+        "lozgrep grep --query=-5 --path=/home/user/file",
+        "lozgrep -sg -- --help /home/user/file",
+    ];
+
+    const FIND_EXAMPLES: [&str; 8] = [
+        "lozgrep find -fo -p /home/user -q filename",
+        "lozgrep find -ofs filename /",
+        "lozgrep find --limit 1 core.dump /var/crash",
+        "lozgrep find -es -p / -q filename",
+        "lozgrep find -p /var/log -q .log -sz +10M -mt +30d",
+        "lozgrep find -ar app.conf /srv/releases",
+        "lozgrep -sf -p /home/user/project -q test -t rust,py -tn json",
+        "lozgrep -sf -q filename @arguments.txt",
+    ];
+
+    const INDEX_EXAMPLES: [&str; 3] = [
+        "lozgrep index ~/src/monorepo",
+        "lozgrep grep --indexed handle_request ~/src/monorepo",
+        "lozgrep grep -ix -t rust -f deprecated_functions.txt ~/src/monorepo",
+    ];
+
+    const UPDATEDB_EXAMPLES: [&str; 3] = [
+        "lozgrep updatedb /",
+        "lozgrep find --locate nginx.conf /etc",
+        "lozgrep find -lo -ma 7d -t rust main ~/src",
+    ];
+
+    const REPLACE_EXAMPLES: [&str; 4] = [
+        "lozgrep replace old_name new_name src/*.rs",
+        "lozgrep replace old_name new_name src/*.rs | patch -p0",
+        "lozgrep replace -i -q old_name -rw new_name src/main.rs",
+        "lozgrep replace -i --regex 'fn (\\w+)_old\\(' 'fn ${1}_new(' src/*.rs",
+    ];
+
+    const GENERAL_EXAMPLES: [&str; 5] = [
+        "lozgrep help find",
+        "lozgrep find --help",
+        "lozgrep --help -ver --query filename --simple-find -p /root",
+        "lozgrep -gmp > lozgrep.1",
+        "lozgrep -gc fish > ~/.config/fish/completions/lozgrep.fish",
+    ];
+
+    const ESCAPE_RULES: [&str; 4] = [
+        "The escape character is: /",
+        "The escape character can only be used on the value you want to query.",
+        "The escape character is not needed anymore, because a value always belongs to the option before it. It is still removed so older scripts keep working.",
+        "Therefore if you need to query for / you need to escape it, otherwise it will be stripped and the query will be empty.",
+    ];
+
+    const ESCAPE_EXAMPLES: [&str; 3] = [
+        "lozgrep -sg -q /--help -p /home/user/file",
+        "Is the same as: lozgrep -sg -q --help -p /home/user/file",
+        "lozgrep -sg -q // -p /home/user/file",
+    ];
+
+    pub fn write_line(borrow_arguments: fmt::Arguments) { // Used by print_line!. stdout is line buffered, so the line is written in one piece as soon as it is finished, also when the output goes into a pipe.
+        let mut stdout: io::StdoutLock = io::stdout().lock();
+        let written: io::Result<()> = stdout.write_fmt(borrow_arguments).and_then(|_| stdout.write_all(b"\n"));
+
+        match written {
+            Ok(()) => {}
+            Err(error_one) if error_one.kind() == io::ErrorKind::BrokenPipe => { process::exit(1); } // lozgrep grep error app.log | head -5. head has every line it wanted and closed the pipe, that is not an error worth printing.
+            Err(error_one) => { // A full disk or a failing device. stdout is the problem, so stderr is the only place left to say so.
+                eprintln!("Error. Problem writing the output: {}", error_one);
+                process::exit(1);
+            }
+        }
+    }
+
+    pub fn print_version(borrow_passed_options_verbose: &bool) {
+        print_line!("lozgrep version {}", VERSION);
+
+        if borrow_passed_options_verbose == &true { // The build information is needed for bug reports. It is captured by build.rs when lozgrep is compiled.
+            print_line!("features: {}", env!("LOZGREP_FEATURES"));
+            print_line!("target: {}", env!("LOZGREP_TARGET"));
+            print_line!("git commit: {}", env!("LOZGREP_GIT_COMMIT"));
+            if let Some(build_date) = option_env!("LOZGREP_BUILD_DATE") { print_line!("build date: {}", build_date); } // Only set when SOURCE_DATE_EPOCH was set for the build.
+            print_line!("VERBOSE: Printed version");
+        }
+    }
+
+    pub fn print_help(borrow_passed_options_verbose: &bool, borrow_help_topic: &Option<OptionScope>) { // None prints everything, Some(Grep) or Some(Find) prints the help for that mode.
+        match borrow_help_topic {
+            Some(OptionScope::Grep) => { print_mode_help(GREP_USAGE, &GREP_DESCRIPTION, OptionScope::Grep, &GREP_EXAMPLES); }
+            Some(OptionScope::Find) => { print_mode_help(FIND_USAGE, &FIND_DESCRIPTION, OptionScope::Find, &FIND_EXAMPLES); }
+            Some(OptionScope::Replace) => { print_mode_help(REPLACE_USAGE, &REPLACE_DESCRIPTION, OptionScope::Replace, &REPLACE_EXAMPLES); }
+            Some(OptionScope::Index) => { print_mode_help(INDEX_USAGE, &INDEX_DESCRIPTION, OptionScope::Index, &INDEX_EXAMPLES); }
+            Some(OptionScope::Updatedb) => { print_mode_help(UPDATEDB_USAGE, &UPDATEDB_DESCRIPTION, OptionScope::Updatedb, &UPDATEDB_EXAMPLES); }
+            _ => { print_full_help(); }
+        }
+
+        if borrow_passed_options_verbose == &true { print_line!("VERBOSE: Printed help menu"); }
+    }
+
+    fn print_full_help() {
+        print_section("Usage:", &[GREP_USAGE, FIND_USAGE, REPLACE_USAGE, INDEX_USAGE, UPDATEDB_USAGE, HELP_USAGE]);
+        print_commands();
+        print_options(|_| true);
+        print_section("Syntax rules:", &SYNTAX_RULES);
+        print_section("Config file:", &CONFIG_FILE_RULES);
+        print_section("Environment variables:", &ENVIRONMENT_RULES);
+        print_section("Examples:", &[&GREP_EXAMPLES[..], &FIND_EXAMPLES[..], &REPLACE_EXAMPLES[..], &INDEX_EXAMPLES[..], &UPDATEDB_EXAMPLES[..], &GENERAL_EXAMPLES[..]].concat());
+        print_section("Escape character rules:", &ESCAPE_RULES);
+        print_section("Escape character examples:", &ESCAPE_EXAMPLES);
+        print_line!("Use \"lozgrep help\" followed by grep, find, replace, index or updatedb to see the options of one command.");
+    }
+
+    fn print_mode_help(borrow_usage: &str, borrow_description: &[&str], borrow_scope: OptionScope, borrow_examples: &[&str]) {
+        print_section("Usage:", &[borrow_usage]);
+        print_section("Description:", borrow_description);
+        print_options(|spec| spec.scope.applies_to(borrow_scope));
+        print_section("Examples:", borrow_examples);
+        print_line!("Use \"lozgrep help\" to see the syntax rules, the config file and the environment variables.");
+    }
+
+    fn print_commands() {
+        print_line!("Commands:");
+        for (command, long, _, description) in COMMAND_TABLE { print_line!("{:<9}{}  Same as {}.", command, description, long); }
+        print_line!("");
+    }
+
+    fn print_section(borrow_title: &str, borrow_lines: &[&str]) {
+        print_line!("{}", borrow_title);
+        for line in borrow_lines { print_line!("{}", line); }
+        print_line!("");
+    }
+
+    fn print_options(borrow_filter: impl Fn(&OptionSpec) -> bool) { // The column widths are taken from the whole table, so every help page lines up the same way.
+        let long_width: usize = OPTION_TABLE.iter().map(|spec| long_with_value(spec).len()).max().unwrap_or(0) + 2;
+        let short_width: usize = OPTION_TABLE.iter().map(|spec| spec.short.len()).max().unwrap_or(0) + 2;
+
+        print_line!("Options:");
+        for spec in OPTION_TABLE.iter().filter(|spec| borrow_filter(spec)) {
+            print_line!("{:<long_width$}{:<short_width$}{}", long_with_value(spec), spec.short, spec.description);
+            if spec.long == "--type-add" { print_line!("{:<long_width$}{:<short_width$}The built-in types are: {}.", "", "", built_in_type_names()); }
+        }
+        print_line!("");
+    }
+
+    fn long_with_value(borrow_spec: &OptionSpec) -> String { // --query QUERY, or --help if the option does not take a value.
+        if borrow_spec.takes_value == true { return format!("{} {}", borrow_spec.long, borrow_spec.value_name); }
+        return borrow_spec.long.to_string();
+    }
+
+    pub fn print_man_page(borrow_passed_options_verbose: &bool) { // Prints the man page in roff format, it can be read with: man ./lozgrep.1
+        print_line!(".TH LOZGREP 1 \"\" \"lozgrep {}\" \"User Commands\"", VERSION);
+        print_line!(".SH NAME");
+        print_line!("lozgrep \\- searches the contents of files and the names of files");
+
+        print_line!(".SH SYNOPSIS");
+        for usage in [GREP_USAGE, FIND_USAGE, REPLACE_USAGE, INDEX_USAGE, UPDATEDB_USAGE, HELP_USAGE] { print_line!(".PP\n{}", escape_roff(usage)); }
+
+        print_line!(".SH DESCRIPTION");
+        print_line!(".SS grep");
+        for line in GREP_DESCRIPTION { print_line!(".PP\n{}", escape_roff(line)); }
+        print_line!(".SS find");
+        for line in FIND_DESCRIPTION { print_line!(".PP\n{}", escape_roff(line)); }
+        print_line!(".SS replace");
+        for line in REPLACE_DESCRIPTION { print_line!(".PP\n{}", escape_roff(line)); }
+        print_line!(".SS index");
+        for line in INDEX_DESCRIPTION { print_line!(".PP\n{}", escape_roff(line)); }
+        print_line!(".SS updatedb");
+        for line in UPDATEDB_DESCRIPTION { print_line!(".PP\n{}", escape_roff(line)); }
+
+        print_line!(".SH OPTIONS");
+        for spec in OPTION_TABLE.iter() {
+            let value: String = if spec.takes_value == true { format!(" \\fI{}\\fR", spec.value_name) } else { String::new() };
+            print_line!(".TP\n\\fB{}\\fR, \\fB{}\\fR{}\n{}", escape_roff(spec.long), escape_roff(spec.short), value, escape_roff(spec.description));
+        }
+        print_line!(".PP\n{}", escape_roff(&format!("The built-in types are: {}.", built_in_type_names())));
+
+        print_man_section("SYNTAX RULES", &SYNTAX_RULES);
+        print_man_section("CONFIG FILE", &CONFIG_FILE_RULES);
+        print_man_section("ENVIRONMENT", &ENVIRONMENT_RULES);
+
+        print_line!(".SH EXAMPLES");
+        print_man_example_block(&[&GREP_EXAMPLES[..], &FIND_EXAMPLES[..], &REPLACE_EXAMPLES[..], &INDEX_EXAMPLES[..], &UPDATEDB_EXAMPLES[..], &GENERAL_EXAMPLES[..]].concat());
+
+        print_man_section("ESCAPE CHARACTER", &ESCAPE_RULES);
+        print_man_example_block(&ESCAPE_EXAMPLES);
+
+        if borrow_passed_options_verbose == &true { print_line!("VERBOSE: Printed man page"); }
+    }
+
+    fn print_man_section(borrow_title: &str, borrow_lines: &[&str]) {
+        print_line!(".SH {}", borrow_title);
+        for line in borrow_lines { print_line!(".PP\n{}", escape_roff(line)); }
+    }
+
+    fn print_man_example_block(borrow_lines: &[&str]) { // .nf keeps the lines as they are, so every example stays on its own line.
+        print_line!(".PP\n.nf");
+        for line in borrow_lines { print_line!("{}", escape_roff(line)); }
+        print_line!(".fi");
+    }
+
+    fn escape_roff(borrow_line: &str) -> String { // \ starts a roff escape, - would be printed as a hyphen and . or ' at the start of a line would be read as a request.
+        let escaped: String = borrow_line.replace('\\', "\\e").replace('-', "\\-");
+
+        if escaped.starts_with('.') || escaped.starts_with('\'') { return format!("\\&{}", escaped); }
+        return escaped;
+    }
+
+    enum ValueCompletion { // What the shells offer after an option that takes a value.
+        Nothing,
+        Files,
+        Users,
+        Groups,
+        Words(String),
+    }
+
+    fn value_completion(borrow_spec: &OptionSpec) -> ValueCompletion {
+        match borrow_spec.long {
+            "--path" => ValueCompletion::Files,
+            "--user" => ValueCompletion::Users,
+            "--group" => ValueCompletion::Groups,
+            "--type" | "--type-not" => ValueCompletion::Words(built_in_type_names().replace(", ", " ")),
+            "--generate-completions" => ValueCompletion::Words("bash zsh fish".to_string()),
+            _ => ValueCompletion::Nothing, // Queries, sizes, times, modes and type definitions are typed by the user.
+        }
+    }
+
+    fn short_description(borrow_spec: &OptionSpec) -> &'static str { // The first sentence without the period, the full description is too long for a completion menu.
+        let description: &'static str = borrow_spec.description;
+        return description.split(". ").next().unwrap_or(description).trim_end_matches('.');
+    }
+
+    pub fn print_completions(borrow_passed_options_verbose: &bool, borrow_shell: &str) { // The shell was checked by parse_and_build_arguments, so it is bash, zsh or fish.
+        match borrow_shell {
+            "bash" => { print_bash_completions(); }
+            "zsh" => { print_zsh_completions(); }
+            _ => { print_fish_completions(); }
+        }
+
+        if borrow_passed_options_verbose == &true { print_line!("VERBOSE: Printed {} completions", borrow_shell); }
+    }
+
+    fn print_bash_completions() {
+        let all_options: Vec<&str> = OPTION_TABLE.iter().flat_map(|spec| [spec.long, spec.short]).collect();
+
+        print_line!("_lozgrep() {{");
+        print_line!("    local current=\"${{COMP_WORDS[COMP_CWORD]}}\"");
+        print_line!("    local previous=\"${{COMP_WORDS[COMP_CWORD-1]}}\"");
+        print_line!("");
+        print_line!("    if [[ $COMP_CWORD -eq 2 && \"$previous\" == \"help\" ]]; then");
+        print_line!("        COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))", COMMAND_TABLE.map(|(command, _, _, _)| command).join(" "));
+        print_line!("        return");
+        print_line!("    fi");
+        print_line!("");
+        print_line!("    case \"$previous\" in");
+        for spec in OPTION_TABLE.iter().filter(|spec| spec.takes_value == true) {
+            let reply: String = match value_completion(spec) {
+                ValueCompletion::Nothing => String::new(),
+                ValueCompletion::Files => "COMPREPLY=($(compgen -f -- \"$current\")); ".to_string(),
+                ValueCompletion::Users => "COMPREPLY=($(compgen -u -- \"$current\")); ".to_string(),
+                ValueCompletion::Groups => "COMPREPLY=($(compgen -g -- \"$current\")); ".to_string(),
+                ValueCompletion::Words(words) => format!("COMPREPLY=($(compgen -W \"{}\" -- \"$current\")); ", words),
+            };
+            print_line!("        {}|{}) {}return ;;", spec.long, spec.short, reply);
+        }
+        print_line!("    esac");
+        print_line!("");
+        print_line!("    if [[ \"$current\" == -* ]]; then");
+        print_line!("        COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))", all_options.join(" "));
+        print_line!("    else");
+        print_line!("        COMPREPLY=($(compgen -f -- \"$current\"))");
+        print_line!("        if [[ $COMP_CWORD -eq 1 ]]; then COMPREPLY+=($(compgen -W \"{} help\" -- \"$current\")); fi", COMMAND_TABLE.map(|(command, _, _, _)| command).join(" "));
+        print_line!("    fi");
+        print_line!("}}");
+        print_line!("");
+        print_line!("complete -o filenames -F _lozgrep lozgrep");
+    }
+
+    fn print_zsh_completions() {
+        print_line!("#compdef lozgrep");
+        print_line!("");
+        print_line!("_lozgrep() {{");
+        print_line!("    _arguments \\");
+        for spec in OPTION_TABLE.iter() {
+            let exclusion: String = if spec.long == "--path" { "*".to_string() } else { format!("({} {})", spec.long, spec.short) }; // --path can be passed more than once.
+            let description: String = short_description(spec).replace('\'', "'\\''").replace('[', "\\[").replace(']', "\\]");
+            let value: String = if spec.takes_value == false { String::new() } else {
+                let action: String = match value_completion(spec) {
+                    ValueCompletion::Nothing => " ".to_string(),
+                    ValueCompletion::Files => "_files".to_string(),
+                    ValueCompletion::Users => "_users".to_string(),
+                    ValueCompletion::Groups => "_groups".to_string(),
+                    ValueCompletion::Words(words) => format!("({})", words),
+                };
+                format!(":{}:{}", spec.value_name, action)
+            };
+            print_line!("        '{}'{{{},{}}}'[{}]{}' \\", exclusion, spec.long, spec.short, description, value);
+        }
+        print_line!("        '1: :(({}))' \\", COMMAND_TABLE.iter().map(|(command, _, _, description)| format!("{}\\:\"{}\"", command, description.trim_end_matches('.'))).chain([String::from("help\\:\"Prints the help for one command\"")]).collect::<Vec<String>>().join(" "));
+        print_line!("        '*:file:_files'");
+        print_line!("}}");
+        print_line!("");
+        print_line!("_lozgrep \"$@\"");
+    }
+
+    fn print_fish_completions() {
+        for (command, _, _, description) in COMMAND_TABLE { print_line!("complete -c lozgrep -n '__fish_use_subcommand' -a {} -d '{}'", command, description.trim_end_matches('.')); }
+        print_line!("complete -c lozgrep -n '__fish_use_subcommand' -a help -d 'Prints the help for one command'");
+        print_line!("complete -c lozgrep -n '__fish_seen_subcommand_from help' -f -a '{}'", COMMAND_TABLE.map(|(command, _, _, _)| command).join(" "));
+        for spec in OPTION_TABLE.iter() {
+            let value: String = if spec.takes_value == false { String::new() } else {
+                match value_completion(spec) {
+                    ValueCompletion::Nothing => " -x".to_string(),
+                    ValueCompletion::Files => " -r -F".to_string(),
+                    ValueCompletion::Users => " -x -a '(__fish_complete_users)'".to_string(),
+                    ValueCompletion::Groups => " -x -a '(__fish_complete_groups)'".to_string(),
+                    ValueCompletion::Words(words) => format!(" -x -a '{}'", words),
+                }
+            };
+            let description: String = short_description(spec).replace('\\', "\\\\").replace('\'', "\\'");
+            print_line!("complete -c lozgrep -l {} -o {}{} -d '{}'", spec.long.trim_start_matches('-'), spec.short.trim_start_matches('-'), value, description); // -o is the fish name for a short option with more than one letter.
+        }
+    }
+}
+
+pub mod execute_main_operations {
+    use std::io;
+    use std::fmt;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::collections::HashMap;
+    use crate::parse_and_build_arguments::Options;
+    use crate::index_file_contents::{load_index, TrigramIndex, UpdateSummary};
+    use crate::locate_file_names::{build_database, find_database, FileNameDatabase};
+    use std::time::Duration;
+    use crate::replace_in_files::{print_unified_diff, replace_all, write_file_atomically};
+    use crate::decompress_files::{compression_of, decompress_if_compressed, read_file_contents};
+    use crate::read_archive_members::{archive_kind, read_members, virtual_path, ArchiveKind, ArchiveMember};
+    use crate::watch_for_changes::{ChangeWatcher, FollowChange, FollowUpdate, FollowedFile, WatchChanges, POLL_INTERVAL};
+
+    pub fn simple_grep(borrow_passed_options: &Options) { // Takes the whole Options struct, because the operations need to know about most of the options.
+        if borrow_passed_options.watch == true { grep_and_watch(borrow_passed_options); } // Never returns, the process is stopped with Ctrl+C.
+
+        let mut total_matches: usize = 0;
+        let mut files_not_read: usize = 0; // If none of the files could be read, "No matches found." would be misleading.
+
+        if borrow_passed_options.verbose == true && borrow_passed_options.follow == true { print_line!("VERBOSE: Symbolic links will be followed"); }
+
+        for borrow_path_item in &borrow_passed_options.path_items { // Every file is searched on its own, in the order they were passed.
+            let results_left: usize = borrow_passed_options.result_limit - total_matches;
+            let matches: Option<usize> = if borrow_passed_options.indexed == true { grep_indexed_directory(borrow_passed_options, borrow_path_item, results_left) } else { grep_one_file(borrow_passed_options, borrow_path_item, borrow_passed_options.path_items.len() > 1, results_left) };
+            match matches {
+                Some(matches) => { total_matches = total_matches + matches; }
+                None => { files_not_read = files_not_read + 1; }
+            }
+
+            if total_matches >= borrow_passed_options.result_limit { // limit or quiet was reached, the other files are not read.
+                if borrow_passed_options.verbose == true { print_line!("VERBOSE: Reached the limit of {} results", borrow_passed_options.result_limit); }
+                break;
+            }
+        }
+
+        if total_matches == 0 && files_not_read < borrow_passed_options.path_items.len() && borrow_passed_options.quiet == false { print_line!("No matches found."); } // If matches are found, total_matches will not equal zero.
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: End of process, now exiting"); }
+        exit_with_search_result(borrow_passed_options, total_matches);
+    }
+
+    fn exit_with_search_result(borrow_passed_options: &Options, borrow_results: usize) { // With quiet the exit code is the answer: 0 if something was found, 1 if not. Without quiet the exit code is always 1, like every other operation.
+        if borrow_passed_options.quiet == true && borrow_results > 0 { process::exit(0); }
+        process::exit(1);
+    }
+
+    fn print_result(borrow_passed_options: &Options, borrow_result: fmt::Arguments) { // Every line and path grep and find print as a result goes through here, so quiet can leave them out. The result is written out as it is, without building a String first.
+        if borrow_passed_options.quiet == false { print_line!("{}", borrow_result); }
+    }
+
+    fn print_error(borrow_passed_options: &Options, borrow_error: fmt::Arguments) { // Every error and warning grep and find print about one path goes through here. Like grep -qs, quiet leaves them out too, the exit code is the only output.
+        if borrow_passed_options.quiet == false {
+            print_line!("{}", borrow_error);
+        } else if borrow_passed_options.verbose == true {
+            print_line!("VERBOSE: Suppressed an error: {}", borrow_error);
+        }
+    }
+
+    fn grep_one_file(borrow_passed_options: &Options, borrow_path_item: &String, borrow_print_path_prefix: bool, borrow_results_left: usize) -> Option<usize> { // Returns the number of matching lines, or None if the file could not be read. Like grep, the path is put in front of the line when more than one file is searched. At most borrow_results_left lines are printed.
+
+        if borrow_passed_options.search_archives == true {
+            if let Some(kind) = archive_kind(borrow_path_item) { return grep_one_archive(borrow_passed_options, borrow_path_item, kind, borrow_results_left); } // The type options are checked against the members, not the archive.
+        }
+
+        if borrow_passed_options.file_type_filter.file_name_matches(&file_name_of(borrow_path_item)) == false { // The file is not one of the selected types, so it is not searched.
+            if borrow_passed_options.verbose == true { print_line!("VERBOSE: {} does not match the selected file types, it will not be searched", borrow_path_item); }
+            return Some(0);
+        }
+
+        let contents_result: Result<String, io::Error> = read_file_contents(borrow_path_item, borrow_passed_options.search_zip); // Takes the file_path, opens that file, and returns a value of type std::io::Result<String> that contains the file’s contents. With search-zip a compressed file is decompressed first.
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Attempt to read the contents of {} into a string", borrow_path_item); }
+
+        let file_contents:String = match contents_result { // Begins a match expression to handle the two possible variants of the contents_result (Ok or Err). Declares contents as a String to store the file contents if reading is successful.
+            Ok(file) => file, // If no error, the contents of the file are passed into file_contents.
+            Err(error_one) => { // If error, print error message and move on to the next file.
+            if error_one.kind() == io::ErrorKind::InvalidData && borrow_passed_options.search_zip == false && looks_compressed(borrow_path_item) == true {
+                print_error(borrow_passed_options, format_args!("Error. Problem reading the file contents of the given path {}: {}. The file looks compressed, use \"--search-zip\" or \"-z\" to search the text inside.", borrow_path_item, error_one));
+                return None;
+            }
+            print_error(borrow_passed_options, format_args!("Error. Problem reading the file contents of the given path {}: {}", borrow_path_item, error_one));
+            return None;
+            }
+        };
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Successfully read the contents of {} into a string", borrow_path_item); }
+
+        return Some(print_matching_lines(borrow_passed_options, borrow_path_item, &file_contents, borrow_print_path_prefix, borrow_results_left.min(borrow_passed_options.max_count)));
+    }
+
+    fn grep_and_watch(borrow_passed_options: &Options) { // Runs until the process is stopped. The files are searched once and then followed, the files in the directories are followed from their first change on.
+        let mut watcher: ChangeWatcher = match ChangeWatcher::new() {
+            Ok(watcher) => watcher,
+            Err(error_one) => {
+                print_line!("Error. Problem starting the file watcher: {}", error_one);
+                process::exit(1);
+            }
+        };
+
+        let mut followed_files: Vec<FollowedFile> = Vec::new();
+        let mut changed_files: HashMap<PathBuf, FollowedFile> = HashMap::new(); // The files in the watched directories that changed, by their canonical path. The first change searches the whole file, every later change only the lines that were appended.
+        let mut watched_directories: Vec<(String, PathBuf)> = Vec::new(); // (the directory as it was passed, its canonical path). The watcher reports absolute paths, they are printed below the directory as it was passed.
+
+        for borrow_path_item in &borrow_passed_options.path_items {
+            let path: &Path = Path::new(borrow_path_item);
+
+            if path.is_dir() {
+                match fs::canonicalize(path).and_then(|canonical_path| watcher.watch(&canonical_path, true).map(|_| canonical_path)) {
+                    Ok(canonical_path) => { watched_directories.push((borrow_path_item.clone(), canonical_path)); }
+                    Err(error_one) => { print_error(borrow_passed_options, format_args!("Error. Problem watching the directory {}: {}", borrow_path_item, error_one)); }
+                }
+                continue;
+            }
+
+            let parent: &Path = match path.parent() { // The directory is watched instead of the file, so a rotated file is seen when it is created again.
+                Some(parent) if parent.as_os_str().is_empty() == false => parent,
+                _ => Path::new("."),
+            };
+            if let Err(error_one) = watcher.watch(parent, false) { print_error(borrow_passed_options, format_args!("Error. Problem watching the directory of {}: {}. It is checked every second instead.", borrow_path_item, error_one)); }
+            followed_files.push(FollowedFile::new(borrow_path_item));
+        }
+
+        if followed_files.is_empty() && watched_directories.is_empty() { process::exit(1); } // Every path already printed why it cannot be watched.
+
+        let print_path_prefix: bool = borrow_passed_options.path_items.len() > 1 || watched_directories.is_empty() == false;
+        let mut total_matches: usize = 0; // For limit and quiet, the watch ends when enough matches were printed.
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Following {} files and watching {} directories, stop with Ctrl+C", followed_files.len(), watched_directories.len()); }
+
+        loop {
+            for followed_file in &mut followed_files { // Checked on every wake up, not only when an event names the file. Missed events then only delay the lines.
+                match followed_file.read_new_lines() {
+                    Ok(FollowUpdate { lines, change }) => {
+                        match change {
+                            Some(FollowChange::Truncated) => { print_error(borrow_passed_options, format_args!("Warning. {} was truncated, it is followed from the start.", followed_file.path)); }
+                            Some(FollowChange::Replaced) => { print_error(borrow_passed_options, format_args!("Warning. {} was replaced, the new file is followed from the start.", followed_file.path)); }
+                            Some(FollowChange::Gone) => { print_error(borrow_passed_options, format_args!("Warning. {} was moved or deleted, waiting for it to come back.", followed_file.path)); }
+                            None => {}
+                        }
+                        total_matches = total_matches + print_matching_lines(borrow_passed_options, &followed_file.path, &lines, print_path_prefix, borrow_passed_options.result_limit - total_matches);
+                        if total_matches >= borrow_passed_options.result_limit { exit_with_search_result(borrow_passed_options, total_matches); }
+                    }
+                    Err(error_one) => { print_error(borrow_passed_options, format_args!("Error. Problem reading the file contents of the given path {}: {}", followed_file.path, error_one)); }
+                }
+            }
+
+            let changes: WatchChanges = watcher.next_changes(POLL_INTERVAL);
+            for error_one in &changes.errors { print_error(borrow_passed_options, format_args!("Error. Problem watching for changes: {}", error_one)); }
+
+            for changed_path in &changes.paths {
+                let canonical_path: PathBuf = match (changed_path.parent().map(fs::canonicalize), changed_path.file_name()) { // Only the directory is resolved, a symbolic link in it stays a link.
+                    (Some(Ok(parent)), Some(file_name)) => parent.join(file_name),
+                    _ => { continue; } // The directory was removed right after the file was written.
+                };
+                let path_string: String = match watched_directories.iter().find_map(|(path_item, directory)| canonical_path.strip_prefix(directory).ok().map(|relative_path| Path::new(path_item).join(relative_path))) {
+                    Some(display_path) => display_path.to_string_lossy().into_owned(),
+                    None => { continue; } // A file next to a followed file.
+                };
+                if followed_files.iter().any(|followed_file| fs::canonicalize(&followed_file.path).ok().as_ref() == Some(&canonical_path)) { continue; } // Already followed, searching it again would print its old lines twice.
+
+                if is_symbolic_link(&path_string) == true && borrow_passed_options.follow == false { continue; } // A link found in a watched directory is only followed with --follow, a link named on the command line always is.
+                if canonical_path.is_file() == false { continue; } // A new directory, or a file that was removed right after it was written.
+
+                let searched_whole: bool = (borrow_passed_options.search_archives == true && archive_kind(&path_string).is_some()) || (borrow_passed_options.search_zip == true && looks_compressed(&path_string) == true); // A compressed file cannot be read from the middle, so it is searched again from the start.
+                if searched_whole == true {
+                    if borrow_passed_options.verbose == true { print_line!("VERBOSE: {} changed, it will be searched again", path_string); }
+                    total_matches = total_matches + grep_one_file(borrow_passed_options, &path_string, true, borrow_passed_options.result_limit - total_matches).unwrap_or(0);
+                    if total_matches >= borrow_passed_options.result_limit { exit_with_search_result(borrow_passed_options, total_matches); }
+                    continue;
+                }
+                if borrow_passed_options.file_type_filter.file_name_matches(&file_name_of(&path_string)) == false { continue; }
+
+                let changed_file: &mut FollowedFile = changed_files.entry(canonical_path).or_insert_with(|| FollowedFile::new(&path_string));
+                if borrow_passed_options.verbose == true { print_line!("VERBOSE: {} changed, its new lines will be searched", path_string); }
+                match changed_file.read_new_lines() { // A file that was truncated or replaced is read from the start again.
+                    Ok(FollowUpdate { lines, .. }) => {
+                        total_matches = total_matches + print_matching_lines(borrow_passed_options, &path_string, &lines, true, borrow_passed_options.result_limit - total_matches);
+                        if total_matches >= borrow_passed_options.result_limit { exit_with_search_result(borrow_passed_options, total_matches); }
+                    }
+                    Err(error_one) => { print_error(borrow_passed_options, format_args!("Error. Problem reading the file contents of the given path {}: {}", path_string, error_one)); }
+                }
+            }
+        }
+    }
+
+    fn grep_indexed_directory(borrow_passed_options: &Options, borrow_path_item: &String, borrow_results_left: usize) -> Option<usize> { // Only the files the index lists as candidates are read. Returns None if the directory has no index.
+        let mut index: TrigramIndex = match open_index(borrow_path_item) {
+            Ok(Some(index)) => index,
+            Ok(None) => {
+                print_error(borrow_passed_options, format_args!("Error. The directory {} has no index yet. Run \"lozgrep index {}\" first.", borrow_path_item, borrow_path_item));
+                return None;
+            }
+            Err(error_one) => {
+                print_error(borrow_passed_options, format_args!("Error. Problem reading the index of {}: {}", borrow_path_item, error_one));
+                return None;
+            }
+        };
+
+        let summary: UpdateSummary = index.update(borrow_passed_options.follow); // Files that changed since the last search are read again, so the index never hides a match.
+        if summary.read > 0 || summary.removed > 0 {
+            if borrow_passed_options.verbose == true { print_line!("VERBOSE: {} files changed and {} were removed since the index was written, the index will be updated", summary.read, summary.removed); }
+            if let Err(error_one) = index.save() { print_error(borrow_passed_options, format_args!("Error. Problem writing the index of {}: {}", borrow_path_item, error_one)); } // The search can still go on with the index in memory.
+        }
+
+        let candidates: Vec<usize> = index.candidates(&borrow_passed_options.pattern_matcher.patterns, borrow_passed_options.regex);
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: The index narrowed {} files down to {} candidates", index.files.len(), candidates.len()); }
+
+        let mut total_matches: usize = 0;
+
+        for id in candidates {
+            if total_matches >= borrow_results_left { break; }
+            let file_path: String = Path::new(borrow_path_item).join(&index.files[id].path).to_string_lossy().into_owned(); // Printed the way the directory was passed, like find does.
+            if borrow_passed_options.file_type_filter.file_name_matches(&file_name_of(&file_path)) == false { continue; }
+
+            match fs::read_to_string(&file_path) {
+                Ok(file_contents) => { total_matches = total_matches + print_matching_lines(borrow_passed_options, &file_path, &file_contents, true, (borrow_results_left - total_matches).min(borrow_passed_options.max_count)); }
+                Err(error_one) => { print_error(borrow_passed_options, format_args!("Error. Problem reading the file contents of the given path {}: {}", file_path, error_one)); }
+            }
+        }
+
+        return Some(total_matches);
+    }
+
+    fn open_index(borrow_path_item: &String) -> io::Result<Option<TrigramIndex>> { // The index is kept for the canonical path, so ./src and /home/user/project/src share one index.
+        let root: PathBuf = fs::canonicalize(borrow_path_item)?;
+        return load_index(&root);
+    }
+
+    pub fn simple_index(borrow_passed_options: &Options) { // Builds the index of every directory, or brings an existing index up to date.
+        for borrow_path_item in &borrow_passed_options.path_items {
+            let root: PathBuf = match fs::canonicalize(borrow_path_item) {
+                Ok(root) => root,
+                Err(error_one) => {
+                    print_line!("Error. Problem resolving the given path {}: {}", borrow_path_item, error_one);
+                    continue;
+                }
+            };
+
+            let mut index: TrigramIndex = match load_index(&root) {
+                Ok(Some(index)) => index,
+                Ok(None) => TrigramIndex::empty(root.clone()),
+                Err(error_one) => { // A damaged index is built again from nothing.
+                    print_line!("Warning. {}. It will be built again.", error_one);
+                    TrigramIndex::empty(root.clone())
+                }
+            };
+            if borrow_passed_options.verbose == true { print_line!("VERBOSE: The index of {} has {} files before the update", borrow_path_item, index.files.len()); }
+
+            let summary: UpdateSummary = index.update(borrow_passed_options.follow);
+
+            match index.save() {
+                Ok(location) => {
+                    print_line!("{}: {} files, {} read, {} removed, {} skipped.", borrow_path_item, index.files.len(), summary.read, summary.removed, summary.skipped);
+                    if borrow_passed_options.verbose == true { print_line!("VERBOSE: The index was written to {}", location.display()); }
+                }
+                Err(error_one) => { print_line!("Error. Problem writing the index of {}: {}", borrow_path_item, error_one); }
+            }
+        }
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: End of process, now exiting"); }
+        process::exit(1);
+    }
+
+    fn grep_one_archive(borrow_passed_options: &Options, borrow_path_item: &String, borrow_kind: ArchiveKind, borrow_results_left: usize) -> Option<usize> { // Every member is searched like a file, and printed with its virtual path. Returns None if the archive could not be read.
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Attempt to read the members of the archive {}", borrow_path_item); }
+
+        let members: Vec<ArchiveMember> = match read_members(borrow_path_item, borrow_kind, true) {
+            Ok((members, None)) => members,
+            Ok((members, Some(error_one))) => { // The members before the broken one are still searched.
+                print_error(borrow_passed_options, format_args!("Error. Problem reading the archive {}, the members after the broken one are skipped: {}", borrow_path_item, error_one));
+                members
+            }
+            Err(error_one) => {
+                print_error(borrow_passed_options, format_args!("Error. Problem reading the archive {}: {}", borrow_path_item, error_one));
+                return None;
+            }
+        };
+
+        let mut total_matches: usize = 0;
+
+        for member in members {
+            if total_matches >= borrow_results_left { break; }
+            let member_path: String = virtual_path(borrow_path_item, &member.name);
+            if member.is_directory == true || borrow_passed_options.file_type_filter.file_name_matches(&file_name_of(&member.name)) == false { continue; }
+
+            let member_contents: Vec<u8> = match member.contents {
+                Some(Ok(contents)) if borrow_passed_options.search_zip == true => match decompress_if_compressed(&member.name, contents) { // A .gz inside of a .tar is decompressed too.
+                    Ok(contents) => contents,
+                    Err(error_one) => { print_error(borrow_passed_options, format_args!("Error. Problem decompressing the archive member {}: {}", member_path, error_one)); continue; }
+                },
+                Some(Ok(contents)) => contents,
+                Some(Err(error_one)) => { print_error(borrow_passed_options, format_args!("Error. Problem reading the archive member {}: {}", member_path, error_one)); continue; }
+                None => continue,
+            };
+
+            match String::from_utf8(member_contents) { // Archives are full of images and binaries, so members that are not text are skipped without an error.
+                Ok(file_contents) => { total_matches = total_matches + print_matching_lines(borrow_passed_options, &member_path, &file_contents, true, (borrow_results_left - total_matches).min(borrow_passed_options.max_count)); }
+                Err(_) => { if borrow_passed_options.verbose == true { print_line!("VERBOSE: {} is not UTF-8 text, it will not be searched", member_path); } }
+            }
+        }
+
+        return Some(total_matches);
+    }
+
+    fn print_matching_lines(borrow_passed_options: &Options, borrow_path_item: &String, borrow_file_contents: &str, borrow_print_path_prefix: bool, borrow_max_results: usize) -> usize { // Prints the lines of one file that match, and returns how many there were. The search stops after borrow_max_results lines.
+        let borrow_query_item: &String = &borrow_passed_options.query_item;
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Attempt to check if {} contains {}", borrow_path_item, borrow_query_item); }
+        if borrow_passed_options.only_matching == true || borrow_passed_options.output_template.is_some() { return print_matching_parts(borrow_passed_options, borrow_path_item, borrow_file_contents, borrow_print_path_prefix, borrow_max_results); }
+        if borrow_passed_options.multiline == true { return print_matching_blocks(borrow_passed_options, borrow_path_item, borrow_file_contents, borrow_print_path_prefix, borrow_max_results); }
+        let mut printed_lines: usize = 0;
+
+        for item in borrow_passed_options.pattern_matcher.matching_lines(borrow_file_contents).take(borrow_max_results) { // The whole file is searched for the query_item, or any line of the patterns file. Each line is printed as soon as it is found, before the rest of the file is searched.
+            if borrow_passed_options.verbose == true { print_line!("VERBOSE: Found a match"); }
+            if borrow_print_path_prefix == true { print_result(borrow_passed_options, format_args!("{}:{}", borrow_path_item, item)); } else { print_result(borrow_passed_options, format_args!("{}", item)); }
+            printed_lines = printed_lines + 1;
+        }
+
+        return printed_lines;
+    }
+
+    fn print_matching_blocks(borrow_passed_options: &Options, borrow_path_item: &String, borrow_file_contents: &str, borrow_print_path_prefix: bool, borrow_max_results: usize) -> usize { // For multiline. Every line a match covers is printed with its line number, so the lines of one match can be told apart from the next one. Returns how many blocks there were.
+        let mut printed_blocks: usize = 0;
+
+        for (first_line_number, block) in borrow_passed_options.pattern_matcher.matching_blocks(borrow_file_contents).take(borrow_max_results) { // A block is printed as soon as the next match no longer joins it.
+            for (offset, line) in block.split('\n').enumerate() { // split and not lines, a block of one empty line still has to print that line.
+                let line: &str = line.strip_suffix('\r').unwrap_or(line);
+                if borrow_print_path_prefix == true { print_result(borrow_passed_options, format_args!("{}:{}:{}", borrow_path_item, first_line_number + offset, line)); } else { print_result(borrow_passed_options, format_args!("{}:{}", first_line_number + offset, line)); }
+            }
+            printed_blocks = printed_blocks + 1;
+        }
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Printed {} blocks of matching lines", printed_blocks); }
+        return printed_blocks;
+    }
+
+    fn print_matching_parts(borrow_passed_options: &Options, borrow_path_item: &String, borrow_file_contents: &str, borrow_print_path_prefix: bool, borrow_max_results: usize) -> usize { // For only-matching and output. Every match is printed on its own line, instead of the line it is on. Returns how many matches were printed.
+        let borrow_template: &str = borrow_passed_options.output_template.as_deref().unwrap_or("");
+        let sections: Box<dyn Iterator<Item = &str>> = if borrow_passed_options.multiline == true { Box::new(std::iter::once(borrow_file_contents)) } else { Box::new(borrow_passed_options.pattern_matcher.matching_lines(borrow_file_contents)) }; // Without multiline every matching line is searched on its own, so a match never goes over a line break.
+        let mut printed_matches: usize = 0;
+
+        for section in sections {
+            if printed_matches >= borrow_max_results { break; }
+            for (match_start, match_end, filled_template) in borrow_passed_options.pattern_matcher.replacements(section, borrow_template) { // The same match spans replace uses, the template is filled in like a replacement.
+                if match_end == match_start { continue; } // An empty match has nothing to print, like grep -o.
+                if printed_matches >= borrow_max_results { break; }
+
+                let part: &str = if borrow_passed_options.output_template.is_some() { &filled_template } else { &section[match_start..match_end] };
+                if borrow_print_path_prefix == true { print_result(borrow_passed_options, format_args!("{}:{}", borrow_path_item, part)); } else { print_result(borrow_passed_options, format_args!("{}", part)); }
+                printed_matches = printed_matches + 1;
+            }
+        }
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Printed {} matches of {}", printed_matches, borrow_path_item); }
+        return printed_matches;
+    }
+
+    pub fn simple_replace(borrow_passed_options: &Options) { // Prints the changes as a unified diff. With --in-place the files are rewritten instead.
+        let mut total_replacements: usize = 0;
+        let mut files_not_read: usize = 0; // If none of the files could be read, "No matches found." would be misleading.
+
+        if borrow_passed_options.verbose == true && borrow_passed_options.follow == true { print_line!("VERBOSE: Symbolic links will be followed"); }
+
+        for borrow_path_item in &borrow_passed_options.path_items { // Every file is replaced on its own, in the order they were passed.
+            match replace_one_file(borrow_passed_options, borrow_path_item) {
+                Some(replacements) => { total_replacements = total_replacements + replacements; }
+                None => { files_not_read = files_not_read + 1; }
+            }
+        }
+
+        if total_replacements == 0 && files_not_read < borrow_passed_options.path_items.len() { print_line!("No matches found."); }
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: End of process, now exiting"); }
+        process::exit(1);
+    }
+
+    fn replace_one_file(borrow_passed_options: &Options, borrow_path_item: &String) -> Option<usize> { // Returns the number of replacements, or None if the file could not be read or written.
+        let borrow_replacement_item: &str = borrow_passed_options.replacement_item.as_deref().unwrap_or("");
+
+        if borrow_passed_options.search_archives == true && archive_kind(borrow_path_item).is_some() { // Writing a member back would mean rebuilding the whole archive.
+            print_line!("Error. The given path is an archive: {}. replace does not change the members of an archive.", borrow_path_item);
+            return None;
+        }
+
+        if borrow_passed_options.file_type_filter.file_name_matches(&file_name_of(borrow_path_item)) == false { // The file is not one of the selected types, so it is not changed.
+            if borrow_passed_options.verbose == true { print_line!("VERBOSE: {} does not match the selected file types, it will not be changed", borrow_path_item); }
+            return Some(0);
+        }
+
+        let file_contents: String = match fs::read_to_string(borrow_path_item) {
+            Ok(file) => file,
+            Err(error_one) => {
+            print_line!("Error. Problem reading the file contents of the given path {}: {}", borrow_path_item, error_one);
+            return None;
+            }
+        };
+
+        let replacements: Vec<(usize, usize, String)> = borrow_passed_options.pattern_matcher.replacements(&file_contents, borrow_replacement_item);
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Found {} matches in {}", replacements.len(), borrow_path_item); }
+        if replacements.is_empty() { return Some(0); }
+
+        if borrow_passed_options.in_place == false {
+            print_unified_diff(borrow_path_item, &file_contents, &replacements);
+            return Some(replacements.len());
+        }
+
+        let target: PathBuf = match fs::canonicalize(borrow_path_item) { // A link named on the command line is followed, the file it points to is rewritten and the link stays.
+            Ok(target) => target,
+            Err(error_one) => {
+                print_line!("Error. Problem resolving the given path {}: {}", borrow_path_item, error_one);
+                return None;
+            }
+        };
+
+        if let Err(error_one) = write_file_atomically(&target, &replace_all(&file_contents, &replacements)) {
+            print_line!("Error. Problem writing the replaced contents of the given path {}: {}", borrow_path_item, error_one);
+            return None;
+        }
+
+        print_line!("{}: {} {}", borrow_path_item, replacements.len(), if replacements.len() == 1 { "replacement" } else { "replacements" });
+        return Some(replacements.len());
+    }
+
+    pub fn simple_find(borrow_passed_options: &Options) {
+        use walkdir::WalkDir; //  Cross platform Rust library for efficiently walking a directory recursively.
+
+        let mut nothing_found: usize = 0;
+        let mut databases_not_read: usize = 0; // With locate, if none of the filename databases could be read, "No matches found." would be misleading.
+        let mut skipped_entries: Vec<String> = Vec::new(); // Holds the error messages of the entries that could not be walked, used by error-summary and list-errors.
+
+        if borrow_passed_options.verbose == true && borrow_passed_options.follow == true { print_line!("VERBOSE: Symbolic links will be followed"); }
+
+        for borrow_path_item in &borrow_passed_options.path_items { // Every path is walked on its own, in the order they were passed.
+            if borrow_passed_options.locate == true {
+                match locate_in_database(borrow_passed_options, borrow_path_item, borrow_passed_options.result_limit - nothing_found) {
+                    Some(matches) => { nothing_found = nothing_found + matches; }
+                    None => { databases_not_read = databases_not_read + 1; }
+                }
+                if nothing_found >= borrow_passed_options.result_limit { break; }
+                continue;
+            }
+
+            let root_device: u64 = match fs::metadata(borrow_path_item) { // The device ID of the starting directory, every entry is compared against it when one-file-system is used.
+                Ok(metadata) => device_id(&metadata),
+                Err(error_one) => {
+                    print_error(borrow_passed_options, format_args!("Error. Problem reading the metadata of the given path {}: {}", borrow_path_item, error_one));
+                    continue; // The other paths can still be walked.
+                }
+            };
+            if borrow_passed_options.verbose == true && borrow_passed_options.one_file_system == true { print_line!("VERBOSE: Will not descend into directories on a file system other than device {}", root_device); }
+
+            let mut walker = WalkDir::new(borrow_path_item).follow_links(borrow_passed_options.follow).into_iter(); // When follow_links is true, walkdir keeps track of the directories above the current entry and returns an error instead of looping forever.
+
+            while let Some(path_result) = walker.next() { // A while loop is used instead of a for loop, because walker.skip_current_dir() has to be called from inside of the loop.
+                match path_result { // WalkDir::new(borrow_path_item) return a result.
+                    Ok(path) => { // If the process is able to sucessfully access the path.
+                        let path_compare = format!("{}", path.path().display()); // Format the path into a string.
+
+                        if borrow_passed_options.one_file_system == true && path.file_type().is_dir() { // Like find -xdev, the mount point itself is still matched, but its contents are not walked.
+                            if let Ok(metadata) = path.metadata() {
+                                if device_id(&metadata) != root_device {
+                                    if borrow_passed_options.verbose == true { print_line!("VERBOSE: {} is on a different file system, it will not be descended into", path_compare); }
+                                    walker.skip_current_dir();
+                                }
+                            }
+                        }
+
+                        let type_matches: bool = borrow_passed_options.file_type_filter.is_empty() || (path.file_type().is_file() && borrow_passed_options.file_type_filter.file_name_matches(&path.file_name().to_string_lossy())); // Types only apply to files. file_type() is the type of the link itself, unless links are followed, so an unfollowed link to a file is not matched.
+
+                        if borrow_passed_options.pattern_matcher.is_match(path_compare.as_bytes()) && type_matches == true && borrow_passed_options.metadata_filters.entry_matches(&path) == true { // Check if the query_item is contained within the path, and if the entry passes the type and metadata options. The metadata is only read if the name matches.
+                            if borrow_passed_options.verbose == true { print_line!("VERBOSE: Found a match"); }
+                            print_result(borrow_passed_options, format_args!("{}", path_compare)); // Print the path that matched.
+                            nothing_found = nothing_found + 1;
+                        }
+
+                        if borrow_passed_options.search_archives == true && path.file_type().is_file() && nothing_found < borrow_passed_options.result_limit {
+                            if let Some(kind) = archive_kind(&path_compare) { nothing_found = nothing_found + find_in_archive(borrow_passed_options, &path, &path_compare, kind, &mut skipped_entries, borrow_passed_options.result_limit - nothing_found); }
+                        }
+
+                        if nothing_found >= borrow_passed_options.result_limit { break; } // limit or quiet was reached, the rest of the tree is not walked.
+
+                    } Err(error_one) => {
+                        let error_message: String = if let Some(ancestor) = error_one.loop_ancestor() { // Only happens when following links. The link points back to a directory that is already being walked, so it is skipped.
+                            format!("Warning. Symbolic link loop detected: {} points to {}, it will not be followed.", error_one.path().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown path".to_string()), ancestor.display())
+                        } else if let Some(inner) = error_one.io_error() { // This checks if err.io_error() has a detailed I/O error (Some(inner)), which can be further analyzed.
+                            if inner.kind() == std::io::ErrorKind::PermissionDenied { // If it finds an I/O error (Some(inner)), it inspects the error's kind() (like PermissionDenied).
+                                format!("Permission denied: {}", error_one.path().map(|p| p.display().to_string()).unwrap_or_else(|| "unknown path".to_string())) // map() is a method available on Option. It applies the given closure (the function inside map) to the value inside the Some variant and returns a new Option with the transformed value. If path() returns Some(p), the closure |p| p.display().to_string() converts the Path into a string using the display() method. The result is Some(String). If path() returns None, map() does nothing and the result remains None. .unwrap_or_else(|| "unknown path".to_string()). This method is called on the result of map(), which is an Option<String>. If the Option is Some(String), it extracts and returns the string. If the Option is None, it calls the closure provided to unwrap_or_else and uses its return value. Here, the closure returns the string "unknown path". 
+                            } else {
+                                format!("Error: {}", error_one)
+                            }
+                        } else { // If it's None (i.e., no I/O error is associated with err), it skips this block and prints a generic error message.
+                            format!("Error: {}", error_one)
+                        };
+
+                        report_skipped_entry(borrow_passed_options, error_message, &mut skipped_entries);
+                    }
+                }
+            }
+
+            if nothing_found >= borrow_passed_options.result_limit {
+                if borrow_passed_options.verbose == true { print_line!("VERBOSE: Reached the limit of {} results", borrow_passed_options.result_limit); }
+                break;
+            }
+        }
+
+        if nothing_found == 0 && databases_not_read < borrow_passed_options.path_items.len() && borrow_passed_options.quiet == false {print_line!("No matches found.");} // If matches are found, nothing_found will not equal zero.
+
+        if skipped_entries.len() != 0 { // Only error-summary and list-errors push into skipped_entries.
+            if skipped_entries.len() == 1 { print_line!("Skipped 1 unreadable entry."); } else { print_line!("Skipped {} unreadable entries.", skipped_entries.len()); }
+
+            if borrow_passed_options.list_errors == true {
+                for item in &skipped_entries { print_line!("{}", item); }
+            }
+        }
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: End of process, now exiting"); }
+        exit_with_search_result(borrow_passed_options, nothing_found);
+    }
+
+    fn locate_in_database(borrow_passed_options: &Options, borrow_path_item: &String, borrow_results_left: usize) -> Option<usize> { // Prints the saved paths that match, and returns how many there were, at most borrow_results_left. None if there is no database to read.
+        let database_result = fs::canonicalize(borrow_path_item).and_then(|root| find_database(&root)); // The database is kept for the canonical path, like the trigram index.
+
+        let (database, inside): (FileNameDatabase, String) = match database_result {
+            Ok(Some(found)) => found,
+            Ok(None) => {
+                print_error(borrow_passed_options, format_args!("Error. There is no filename database for {} or a directory above it. Run \"lozgrep updatedb {}\" first.", borrow_path_item, borrow_path_item));
+                return None;
+            }
+            Err(error_one) => {
+                print_error(borrow_passed_options, format_args!("Error. Problem reading the filename database of {}: {}", borrow_path_item, error_one));
+                return None;
+            }
+        };
+
+        if database.age() > borrow_passed_options.max_age { // Files created since the database was saved are missing, and deleted files are still listed.
+            print_error(borrow_passed_options, format_args!("Warning. The filename database of {} was saved {} ago. Run \"lozgrep updatedb {}\" to bring it up to date.", database.root.display(), format_age(database.age()), database.root.display()));
+        }
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: Reading the filename database of {}, {} paths", database.root.display(), database.entries.len()); }
+
+        let mut matches: usize = 0;
+
+        for (relative_path, is_directory) in database.entries_under(&inside) {
+            let path_compare: String = if relative_path.is_empty() { borrow_path_item.clone() } else { Path::new(borrow_path_item).join(relative_path).to_string_lossy().into_owned() }; // Printed the way the directory was passed, like the walk does.
+            let type_matches: bool = borrow_passed_options.file_type_filter.is_empty() || (is_directory == false && borrow_passed_options.file_type_filter.file_name_matches(&file_name_of(&path_compare)));
+
+            if borrow_passed_options.pattern_matcher.is_match(path_compare.as_bytes()) && type_matches == true {
+                print_result(borrow_passed_options, format_args!("{}", path_compare));
+                matches = matches + 1;
+                if matches >= borrow_results_left { break; }
+            }
+        }
+
+        return Some(matches);
+    }
+
+    fn format_age(borrow_age: Duration) -> String { // The largest unit that fits: 3 days, 5 hours, 12 minutes.
+        let seconds: u64 = borrow_age.as_secs();
+        let (amount, unit): (u64, &str) = if seconds >= 86400 { (seconds / 86400, "day") } else if seconds >= 3600 { (seconds / 3600, "hour") } else if seconds >= 60 { (seconds / 60, "minute") } else { (seconds, "second") };
+        return format!("{} {}{}", amount, unit, if amount == 1 { "" } else { "s" });
+    }
+
+    pub fn simple_updatedb(borrow_passed_options: &Options) { // Saves every path under every directory, replacing the database that was there.
+        for borrow_path_item in &borrow_passed_options.path_items {
+            let root: PathBuf = match fs::canonicalize(borrow_path_item) {
+                Ok(root) => root,
+                Err(error_one) => {
+                    print_line!("Error. Problem resolving the given path {}: {}", borrow_path_item, error_one);
+                    continue;
+                }
+            };
+
+            let (database, skipped): (FileNameDatabase, usize) = build_database(&root, borrow_passed_options.follow);
+
+            match database.save() {
+                Ok(location) => {
+                    print_line!("{}: {} paths, {} skipped.", borrow_path_item, database.entries.len(), skipped);
+                    if borrow_passed_options.verbose == true { print_line!("VERBOSE: The filename database was written to {}", location.display()); }
+                }
+                Err(error_one) => { print_line!("Error. Problem writing the filename database of {}: {}", borrow_path_item, error_one); }
+            }
+        }
+
+        if borrow_passed_options.verbose == true { print_line!("VERBOSE: End of process, now exiting"); }
+        process::exit(1);
+    }
+
+    fn find_in_archive(borrow_passed_options: &Options, borrow_entry: &walkdir::DirEntry, borrow_path_compare: &String, borrow_kind: ArchiveKind, borrow_skipped_entries: &mut Vec<String>, borrow_results_left: usize) -> usize { // Prints the virtual paths of the members that match, and returns how many there were, at most borrow_results_left. The metadata options are checked against the archive itself, the members have no metadata of their own on disk.
+        if borrow_passed_options.metadata_filters.is_empty() == false && borrow_passed_options.metadata_filters.entry_matches(borrow_entry) == false { return 0; }
+
+        let members: Vec<ArchiveMember> = match read_members(borrow_path_compare, borrow_kind, false) {
+            Ok((members, None)) => members,
+            Ok((members, Some(error_one))) => { // The members before the broken one are still matched.
+                report_skipped_entry(borrow_passed_options, format!("Error: cannot read the rest of the archive {}: {}", borrow_path_compare, error_one), borrow_skipped_entries);
+                members
+            }
+            Err(error_one) => {
+                report_skipped_entry(borrow_passed_options, format!("Error: cannot read the archive {}: {}", borrow_path_compare, error_one), borrow_skipped_entries);
+                return 0;
+            }
+        };
+
+        let mut matches: usize = 0;
+
+        for member in members {
+            let member_path: String = virtual_path(borrow_path_compare, &member.name);
+            let type_matches: bool = borrow_passed_options.file_type_filter.is_empty() || (member.is_directory == false && borrow_passed_options.file_type_filter.file_name_matches(&file_name_of(&member.name)));
+
+            if borrow_passed_options.pattern_matcher.is_match(member_path.as_bytes()) && type_matches == true {
+                if borrow_passed_options.verbose == true { print_line!("VERBOSE: Found a match"); }
+                print_result(borrow_passed_options, format_args!("{}", member_path));
+                matches = matches + 1;
+                if matches >= borrow_results_left { break; }
+            }
+        }
+
+        return matches;
+    }
+
+    fn report_skipped_entry(borrow_passed_options: &Options, error_message: String, borrow_skipped_entries: &mut Vec<String>) { // quiet-errors and quiet drop the error, error-summary and list-errors save it for the end of the walk, otherwise it is printed right away.
+        if borrow_passed_options.quiet_errors == true || borrow_passed_options.quiet == true { // The error is dropped completely.
+            if borrow_passed_options.verbose == true { print_line!("VERBOSE: Suppressed an error: {}", error_message); }
+        } else if borrow_passed_options.error_summary == true || borrow_passed_options.list_errors == true { // The error is saved and reported after the walk is finished.
+            borrow_skipped_entries.push(error_message);
+        } else {
+            print_line!("{}", error_message);
+        }
+    }
+
+    #[cfg(unix)]
+    fn device_id(borrow_metadata: &fs::Metadata) -> u64 { // The ID of the device that holds the file, two entries with different IDs are on different file systems.
+        use std::os::unix::fs::MetadataExt;
+        return borrow_metadata.dev();
+    }
+
+    #[cfg(not(unix))]
+    fn device_id(_borrow_metadata: &fs::Metadata) -> u64 { // Device IDs are not exposed on this platform, so every entry is treated as being on the same file system.
+        return 0;
+    }
+
+    fn looks_compressed(borrow_path: &String) -> bool { // Only the first bytes are read, the file already failed to read as text.
+        use std::io::Read;
+
+        let mut first_bytes: [u8; 6] = [0; 6];
+        let read_length: usize = fs::File::open(borrow_path).and_then(|mut file| file.read(&mut first_bytes)).unwrap_or(0);
+        return compression_of(borrow_path, &first_bytes[..read_length]).is_some();
+    }
+
+    fn file_name_of(borrow_path: &String) -> String { // The last part of the path, used to compare against the type patterns.
+        match std::path::Path::new(borrow_path).file_name() {
+            Some(file_name) => { return file_name.to_string_lossy().to_string(); }
+            None => { return borrow_path.clone(); }
+        }
+    }
+
+    fn is_symbolic_link(borrow_path: &String) -> bool { // fs::symlink_metadata does not follow the link, so it can tell if the path itself is a link.
+        match fs::symlink_metadata(borrow_path) {
+            Ok(metadata) => { return metadata.file_type().is_symlink(); }
+            Err(_) => { return false; } // The path was already validated, if it cannot be read here the read_to_string error will explain why.
+        }
     }
 }
\ No newline at end of file
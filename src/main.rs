@@ -1,26 +1,48 @@
+#![allow(clippy::bool_comparison)] // Matches the explicit comparison style used in lib.rs.
+
 use lozgrep::parse_and_build_arguments::build_running_configuration;
-use lozgrep::print_to_terminal::{print_help, print_version};
-use lozgrep::execute_main_operations::{simple_grep, simple_find};
+use lozgrep::print_line;
+use lozgrep::print_to_terminal::{print_help, print_version, print_man_page, print_completions};
+use lozgrep::execute_main_operations::{simple_grep, simple_find, simple_replace, simple_index, simple_updatedb};
 
 // TODO: Have two query items. Use find first to reduce down the files. And then use a grep query to search the found files.
 fn main() { // As long as the options all pass through build_running_configuration
     let passed_options: lozgrep::parse_and_build_arguments::Options = build_running_configuration(); // Calls on build_running_configuration to parse, check, and organize all the arguments passed. If there are no errors, passed_options will be assigned a struct that holds the status of all the possible options and values.
-    if passed_options.verbose == true { println!("VERBOSE: Collected {:?}", &passed_options); } // If verbose is true, the debug implementation of Options will be used to print all the values of passed_options.
+    if passed_options.verbose == true { print_line!("VERBOSE: Collected {:?}", &passed_options); } // If verbose is true, the debug implementation of Options will be used to print all the values of passed_options.
 
     if passed_options.version == true { print_version(&passed_options.verbose) } // If version is true, will call on print_version to print the version. Verbose is also passed so print_version can print the verbose output if true.
 
-    if passed_options.help == true { print_help(&passed_options.verbose) } // If help is true, will call on print_help to print the version. Verbose is also passed so print_help can print the verbose output if true.
+    if passed_options.help == true { print_help(&passed_options.verbose, &passed_options.help_topic) } // If help is true, will call on print_help to print the help menu. The help topic picks the full help or the help for one mode.
+
+    if passed_options.generate_man_page == true { print_man_page(&passed_options.verbose) } // Prints the man page in roff format.
+
+    if let Some(shell) = &passed_options.generate_completions { print_completions(&passed_options.verbose, shell) } // Prints the completion script for bash, zsh or fish.
 
-    if passed_options.simple_grep == true { // If everything goes right, simple_grep will terminate the process, therefore no return is needed.
-        if passed_options.verbose == true { println!("VERBOSE: Calling on the simple_grep function"); }
-        simple_grep(&passed_options.query_item, &passed_options.path_item, &passed_options.verbose);
+    if passed_options.simple_grep == true && passed_options.path == true { // If everything goes right, simple_grep will terminate the process, therefore no return is needed. There is no path when only the help of simple-grep was asked for.
+        if passed_options.verbose == true { print_line!("VERBOSE: Calling on the simple_grep function"); }
+        simple_grep(&passed_options);
     }
 
-    if passed_options.simple_find == true { // If everything goes right, simple_find will terminate the process, therefore no return is needed.
-        if passed_options.verbose == true { println!("VERBOSE: Calling on the simple_find function"); }
-        simple_find(&passed_options.query_item, &passed_options.path_item, &passed_options.verbose);
+    if passed_options.simple_find == true && passed_options.path == true { // If everything goes right, simple_find will terminate the process, therefore no return is needed. There is no path when only the help of simple-find was asked for.
+        if passed_options.verbose == true { print_line!("VERBOSE: Calling on the simple_find function"); }
+        simple_find(&passed_options);
     }    
 
-    if passed_options.verbose == true { println!("VERBOSE: End of process, now exiting"); }
-    std::process::exit(1); // If the simple-grep or simple-find options are not used, the process should end here.
+    if passed_options.simple_replace == true && passed_options.path == true { // If everything goes right, simple_replace will terminate the process, therefore no return is needed. There is no path when only the help of simple-replace was asked for.
+        if passed_options.verbose == true { print_line!("VERBOSE: Calling on the simple_replace function"); }
+        simple_replace(&passed_options);
+    }
+
+    if passed_options.simple_index == true && passed_options.path == true { // If everything goes right, simple_index will terminate the process, therefore no return is needed. There is no path when only the help of simple-index was asked for.
+        if passed_options.verbose == true { print_line!("VERBOSE: Calling on the simple_index function"); }
+        simple_index(&passed_options);
+    }
+
+    if passed_options.simple_updatedb == true && passed_options.path == true { // If everything goes right, simple_updatedb will terminate the process, therefore no return is needed. There is no path when only the help of simple-updatedb was asked for.
+        if passed_options.verbose == true { print_line!("VERBOSE: Calling on the simple_updatedb function"); }
+        simple_updatedb(&passed_options);
+    }
+
+    if passed_options.verbose == true { print_line!("VERBOSE: End of process, now exiting"); }
+    std::process::exit(1); // If none of the command options are used, the process should end here.
 }
\ No newline at end of file